@@ -0,0 +1,58 @@
+// Criterion benchmarks of pretty-printing and minimizing.
+//
+// Run with `cargo bench` from this directory.
+
+#[macro_use]
+extern crate criterion;
+extern crate jsonxf;
+extern crate jsonxf_bench;
+
+use criterion::{Criterion, Throughput};
+
+fn formatting(c: &mut Criterion) {
+    for (name, json) in jsonxf_bench::samples().iter() {
+        let mut group = c.benchmark_group(*name);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+        group.bench_function("pretty_print", |b| {
+            let mut fmt = jsonxf::Formatter::pretty_printer();
+            b.iter(|| fmt.format_bytes(json.as_bytes()).unwrap())
+        });
+        group.bench_function("minimize", |b| {
+            let mut fmt = jsonxf::Formatter::minimizer();
+            b.iter(|| fmt.format_bytes(json.as_bytes()).unwrap())
+        });
+        group.bench_function("minimize_stream", |b| {
+            b.iter(|| {
+                let mut output: Vec<u8> = Vec::with_capacity(json.len());
+                jsonxf::minimize_stream(json.as_bytes(), &mut output).unwrap();
+                output
+            })
+        });
+        group.finish();
+    }
+}
+
+// Streams a file through buffers of different sizes.
+fn buffer_capacity(c: &mut Criterion) {
+    let path = std::env::temp_dir().join("jsonxf-bench-strings.json");
+    let json = jsonxf_bench::strings(200_000);
+    std::fs::write(&path, &json).unwrap();
+
+    let mut group = c.benchmark_group("buffer_capacity");
+    group.throughput(Throughput::Bytes(json.len() as u64));
+    for &capacity in [8 * 1024, 64 * 1024, 1024 * 1024].iter() {
+        group.bench_function(format!("{}", capacity), |b| {
+            let mut fmt = jsonxf::Formatter::minimizer();
+            fmt.buffer_capacity = capacity;
+            b.iter(|| {
+                let input = std::fs::File::open(&path).unwrap();
+                fmt.format_stream(input, std::io::sink()).unwrap()
+            })
+        });
+    }
+    group.finish();
+    std::fs::remove_file(&path).unwrap();
+}
+
+criterion_group!(benches, formatting, buffer_capacity);
+criterion_main!(benches);
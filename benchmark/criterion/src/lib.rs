@@ -0,0 +1,67 @@
+// Generates the sample documents used by the benchmarks.  Everything
+// here is deterministic, so the same sizes always give the same input.
+
+/// Arrays nested `depth` deep, each holding a number and the next.
+pub fn nested(depth: usize) -> String {
+    let mut json = String::new();
+    for i in 0..depth {
+        json.push_str(&format!("[{}, ", i));
+    }
+    json.push_str("null");
+    for _ in 0..depth {
+        json.push(']');
+    }
+    json
+}
+
+/// A flat array of `count` numbers of different shapes.
+pub fn numbers(count: usize) -> String {
+    let mut json = String::from("[");
+    for i in 0..count {
+        if i > 0 {
+            json.push_str(", ");
+        }
+        match i % 4 {
+            0 => json.push_str(&i.to_string()),
+            1 => json.push_str(&format!("-{}.{}", i, i % 97)),
+            2 => json.push_str(&format!("{}e-{}", i % 1000, i % 17)),
+            _ => json.push_str(&format!("0.{:06}", i % 1_000_000)),
+        }
+    }
+    json.push(']');
+    json
+}
+
+/// An array of `count` objects whose values are mostly strings, some
+/// with escapes and non-ASCII characters.
+pub fn strings(count: usize) -> String {
+    let mut json = String::from("[");
+    for i in 0..count {
+        if i > 0 {
+            json.push_str(", ");
+        }
+        json.push_str(&format!(
+            "{{\"id\": \"user-{}\", \"name\": \"Name {} \\\"Nick\\\" Surname\", \
+             \"bio\": \"Lorem ipsum dolor sit amet, caf\u{e9} {}\\n\\tline two \\u00e9\", \
+             \"path\": \"C:\\\\Users\\\\{}\"}}",
+            i, i, i, i
+        ));
+    }
+    json.push(']');
+    json
+}
+
+/// `strings(count)`, minimized.
+pub fn minimized(count: usize) -> String {
+    jsonxf::minimize(&strings(count)).unwrap()
+}
+
+/// The documents benchmarked, by name.
+pub fn samples() -> Vec<(&'static str, String)> {
+    vec![
+        ("nested", nested(10_000)),
+        ("numbers", numbers(200_000)),
+        ("strings", strings(20_000)),
+        ("minimized", minimized(20_000)),
+    ]
+}
@@ -0,0 +1,25 @@
+// Writes the benchmark sample documents into a directory, so that the
+// same input can be fed to the `jsonxf` binary or to other tools.
+//
+// Usage: cargo run --release -- <directory>
+
+extern crate jsonxf_bench;
+
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let dir = match std::env::args().nth(1) {
+        Some(dir) => dir,
+        None => {
+            eprintln!("usage: jsonxf-bench <directory>");
+            std::process::exit(1);
+        }
+    };
+    fs::create_dir_all(&dir).unwrap();
+    for (name, json) in jsonxf_bench::samples() {
+        let path = Path::new(&dir).join(format!("{}.json", name));
+        fs::write(&path, json).unwrap();
+        println!("wrote {}", path.display());
+    }
+}
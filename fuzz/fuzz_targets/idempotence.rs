@@ -0,0 +1,21 @@
+// Checks that whatever jsonxf accepts, minimizes the same way again,
+// and minimizes the same way after pretty-printing.
+//
+// Run with `cargo fuzz run idempotence` from the repository root.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = match std::str::from_utf8(data) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+    let min = match jsonxf::minimize(input) {
+        Ok(min) => min,
+        Err(_) => return,
+    };
+    let pretty = jsonxf::pretty_print(input).unwrap();
+    assert_eq!(jsonxf::minimize(&min).unwrap(), min);
+    assert_eq!(jsonxf::minimize(&pretty).unwrap(), min);
+});
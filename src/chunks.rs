@@ -0,0 +1,86 @@
+use std::io::prelude::*;
+use std::io::ErrorKind;
+
+use super::{Formatter, JsonxfError};
+
+/// An iterator which reads JSON from a `Read` and yields the formatted
+/// output piece by piece, for pipelines which pull data rather than
+/// having it pushed to a `Write`.
+///
+/// Each item is the output for one buffer of input, so nothing is read
+/// until the next item is asked for.  Buffers which produce no output,
+/// as with `sort_keys`, are skipped.  After an error, the iterator
+/// ends.
+///
+/// # Example:
+///
+/// ```
+/// let input: &[u8] = b"{\"a\": [1, 2]}";
+/// let fmt = jsonxf::Formatter::minimizer();
+/// let mut output: Vec<u8> = vec![];
+/// for chunk in jsonxf::FormattedChunks::new(input, fmt) {
+///     output.extend_from_slice(&chunk.unwrap());
+/// }
+/// assert_eq!(output, b"{\"a\":[1,2]}");
+/// ```
+pub struct FormattedChunks<R: Read> {
+    input: R,
+    formatter: Formatter,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> FormattedChunks<R> {
+    /// Returns an iterator over the output of `formatter` on the JSON
+    /// read from `input`.
+    pub fn new(input: R, mut formatter: Formatter) -> FormattedChunks<R> {
+        formatter.reset();
+        let buf = vec![0; formatter.buffer_capacity.max(1)];
+        FormattedChunks {
+            input,
+            formatter,
+            buf,
+            done: false,
+        }
+    }
+
+    /// Returns a reference to the reader input comes from.
+    pub fn get_ref(&self) -> &R {
+        &self.input
+    }
+
+    // Reads and formats one buffer of input.
+    fn next_chunk(&mut self) -> Result<Vec<u8>, JsonxfError> {
+        let mut output: Vec<u8> = vec![];
+        while output.is_empty() && !self.done {
+            match self.input.read(&mut self.buf) {
+                Ok(0) => {
+                    self.done = true;
+                    self.formatter.finish(&mut output)?;
+                }
+                Ok(n) => self.formatter.format_buf(&self.buf[..n], &mut output)?,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(JsonxfError::Io(e)),
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl<R: Read> Iterator for FormattedChunks<R> {
+    type Item = Result<Vec<u8>, JsonxfError>;
+
+    fn next(&mut self) -> Option<Result<Vec<u8>, JsonxfError>> {
+        if self.done {
+            return None;
+        }
+        match self.next_chunk() {
+            Ok(ref output) if output.is_empty() => None,
+            Ok(output) => Some(Ok(output)),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
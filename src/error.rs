@@ -0,0 +1,173 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::{FromUtf8Error, String};
+#[cfg(feature = "std")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::string::FromUtf8Error;
+
+#[cfg(not(feature = "std"))]
+use io;
+
+/// The error type for Jsonxf operations.
+#[derive(Debug)]
+pub enum JsonxfError {
+    /// Reading input or writing output failed.
+    Io(io::Error),
+
+    /// The output could not be converted to a `String`.
+    Utf8(FromUtf8Error),
+
+    /// In strict mode, the input ended inside `depth` arrays or objects
+    /// after `offset` bytes.
+    UnexpectedEnd { depth: usize, offset: usize },
+
+    /// In strict mode, a closing bracket at byte `offset` did not
+    /// close anything.
+    UnexpectedClose { offset: usize },
+
+    /// In strict mode, the closing bracket `found` at byte `offset` did
+    /// not match the innermost open bracket, which `expected` closes.
+    MismatchedClose {
+        expected: char,
+        found: char,
+        offset: usize,
+    },
+
+    /// In strict mode, the input ended inside the string which began
+    /// at byte `offset`.
+    UnterminatedString { offset: usize },
+
+    /// In strict mode, a string contained the unescaped control
+    /// character `byte` at byte `offset`.
+    ControlCharacter { byte: u8, offset: usize },
+
+    /// In strict mode, a string contained bytes which are not valid
+    /// UTF-8, starting at byte `offset`.
+    InvalidUtf8 { offset: usize },
+
+    /// The JSON Pointer given to `Formatter::format_pointer()` did not
+    /// match anything.
+    PointerNotFound { pointer: String },
+
+    /// The opening bracket at byte `offset` would have nested `depth`
+    /// arrays or objects, more than `Formatter.max_depth` allows.
+    MaxDepthExceeded { depth: usize, offset: usize },
+
+    /// With `Formatter.single_document`, there was more than
+    /// whitespace after the first value, starting at byte `offset`.
+    TrailingData { offset: usize },
+
+    /// With `CommentMode::Reject`, the input had a comment starting
+    /// at byte `offset`.
+    Comment { offset: usize },
+
+    /// With `Formatter.validate_numbers`, the number starting at byte
+    /// `offset` did not follow the JSON grammar.
+    InvalidNumber { offset: usize },
+
+    /// With `Formatter.reject_leading_junk`, the input began with
+    /// `byte` at byte `offset`, which cannot start a value.
+    LeadingJunk { byte: u8, offset: usize },
+
+    /// The output would have been longer than `limit` bytes, the
+    /// `Formatter.max_output_bytes` setting.  Only the first `limit`
+    /// bytes were written.
+    OutputTooLarge { limit: usize },
+
+    /// `canonicalize_jcs()` was given input which has no canonical
+    /// form, for the given `reason`.
+    NotCanonicalizable { reason: String },
+}
+
+impl fmt::Display for JsonxfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonxfError::Io(ref e) => e.fmt(f),
+            JsonxfError::Utf8(ref e) => e.fmt(f),
+            JsonxfError::UnexpectedEnd { depth, offset } => write!(
+                f,
+                "unexpected end of input at byte {} with {} unclosed array(s) or object(s)",
+                offset, depth
+            ),
+            JsonxfError::UnexpectedClose { offset } => {
+                write!(f, "unexpected closing bracket at byte {}", offset)
+            }
+            JsonxfError::MismatchedClose {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "expected `{}` but found `{}` at byte {}",
+                expected, found, offset
+            ),
+            JsonxfError::UnterminatedString { offset } => {
+                write!(f, "unterminated string starting at byte {}", offset)
+            }
+            JsonxfError::ControlCharacter { byte, offset } => write!(
+                f,
+                "unescaped control character 0x{:02x} in string at byte {}",
+                byte, offset
+            ),
+            JsonxfError::InvalidUtf8 { offset } => {
+                write!(f, "invalid UTF-8 in string at byte {}", offset)
+            }
+            JsonxfError::PointerNotFound { ref pointer } => {
+                write!(f, "JSON pointer {:?} does not match anything", pointer)
+            }
+            JsonxfError::MaxDepthExceeded { depth, offset } => write!(
+                f,
+                "maximum nesting depth exceeded at byte {} (depth {})",
+                offset, depth
+            ),
+            JsonxfError::TrailingData { offset } => {
+                write!(
+                    f,
+                    "unexpected data after the first value at byte {}",
+                    offset
+                )
+            }
+            JsonxfError::Comment { offset } => write!(f, "comment at byte {}", offset),
+            JsonxfError::InvalidNumber { offset } => {
+                write!(f, "invalid number at byte {}", offset)
+            }
+            JsonxfError::LeadingJunk { byte, offset } => write!(
+                f,
+                "unexpected byte 0x{:02x} before the first value at byte {}",
+                byte, offset
+            ),
+            JsonxfError::OutputTooLarge { limit } => {
+                write!(f, "output exceeds the limit of {} bytes", limit)
+            }
+            JsonxfError::NotCanonicalizable { ref reason } => {
+                write!(f, "cannot canonicalize: {}", reason)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for JsonxfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            JsonxfError::Io(ref e) => Some(e),
+            JsonxfError::Utf8(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for JsonxfError {
+    fn from(e: io::Error) -> JsonxfError {
+        JsonxfError::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for JsonxfError {
+    fn from(e: FromUtf8Error) -> JsonxfError {
+        JsonxfError::Utf8(e)
+    }
+}
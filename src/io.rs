@@ -0,0 +1,60 @@
+//! Stand-ins for the parts of `std::io` which `Formatter` needs, used
+//! when the `std` feature is disabled.
+
+use alloc::vec::Vec;
+use std::fmt;
+
+/// An error from a [`Write`], for output which can fail to be
+/// written, such as a fixed-size buffer or a serial port.  Writing to
+/// a `Vec<u8>` never fails.
+#[derive(Debug)]
+pub struct Error {
+    message: &'static str,
+}
+
+impl Error {
+    /// Returns an error described by `message`.
+    pub fn new(message: &'static str) -> Error {
+        Error { message }
+    }
+
+    /// Returns the description the error was made with.
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+/// Somewhere for formatted output to go, like `std::io::Write`.
+pub trait Write {
+    /// Writes all of `buf`.
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error>;
+
+    /// Passes on anything held back by a buffer.  Does nothing unless
+    /// overridden.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Write for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        (**self).write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        (**self).flush()
+    }
+}
@@ -0,0 +1,348 @@
+// RFC 8785 JSON Canonicalization Scheme output, built on the tree
+// used for `Formatter.sort_keys`.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use std::fmt::Write;
+use std::ops::ControlFlow;
+
+use super::tree::{unescape, Node, Tree};
+use super::{is_json_number, tokenize, Formatter, JsonxfError, Token};
+
+enum Step {
+    Node(usize),
+    Key(String),
+    Byte(u8),
+}
+
+/// Returns the canonical form of a JSON document under RFC 8785, the
+/// JSON Canonicalization Scheme, for hashing or signing.  Object
+/// members are sorted by the UTF-16 code units of their decoded keys,
+/// there is no whitespace, strings use only the escapes JSON requires,
+/// and numbers are written as ECMAScript writes them.
+///
+/// Input which has no canonical form, such as a number out of range,
+/// a lone surrogate, a duplicate key, a missing or stray comma or
+/// colon, or more than one document, returns
+/// `JsonxfError::NotCanonicalizable`.  Unbalanced brackets are
+/// reported as in `Formatter.strict`.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::canonicalize_jcs("{\"b\": [1.50, 1E21, \"\\u00e9\\/\"], \"a\": true}").unwrap(),
+///     "{\"a\":true,\"b\":[1.5,1e+21,\"é/\"]}"
+/// );
+/// ```
+pub fn canonicalize_jcs(input: &str) -> Result<String, JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    let minimized = xf.format_bytes(input.as_bytes())?;
+    check_structure(std::str::from_utf8(&minimized).unwrap())?;
+    let tree = Tree::parse(&minimized);
+    if tree.roots.len() != 1 {
+        return Err(error(format!(
+            "expected one value, found {}",
+            tree.roots.len()
+        )));
+    }
+
+    let mut output = String::with_capacity(minimized.len());
+    let mut todo = vec![Step::Node(tree.roots[0])];
+    while let Some(step) = todo.pop() {
+        match step {
+            Step::Byte(b) => output.push(b as char),
+            Step::Key(key) => write_string(&key, &mut output),
+            Step::Node(index) => match tree.nodes[index] {
+                Node::Scalar(bytes) => write_scalar(bytes, &mut output)?,
+                Node::Array(ref items) => {
+                    output.push('[');
+                    todo.push(Step::Byte(b']'));
+                    for (i, &item) in items.iter().enumerate().rev() {
+                        todo.push(Step::Node(item));
+                        if i > 0 {
+                            todo.push(Step::Byte(b','));
+                        }
+                    }
+                }
+                Node::Object(ref members) => {
+                    let mut sorted = Vec::with_capacity(members.len());
+                    for member in members {
+                        let bytes = match tree.nodes[member.key] {
+                            Node::Scalar(bytes) => bytes,
+                            _ => &[],
+                        };
+                        let key = match decode_string(bytes) {
+                            Some(key) => key,
+                            None => return Err(invalid("key", bytes)),
+                        };
+                        match member.value {
+                            Some(value) => sorted.push((key, value)),
+                            None => return Err(error(format!("key {:?} has no value", key))),
+                        }
+                    }
+                    sorted.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+                    if let Some(pair) = sorted.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+                        return Err(error(format!("duplicate key {:?}", pair[0].0)));
+                    }
+
+                    output.push('{');
+                    todo.push(Step::Byte(b'}'));
+                    let len = sorted.len();
+                    for (i, (key, value)) in sorted.into_iter().rev().enumerate() {
+                        todo.push(Step::Node(value));
+                        todo.push(Step::Byte(b':'));
+                        todo.push(Step::Key(key));
+                        if i + 1 < len {
+                            todo.push(Step::Byte(b','));
+                        }
+                    }
+                }
+            },
+        }
+    }
+    Ok(output)
+}
+
+// What may come next in `check_structure`.
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    FirstValue, // a value or `]`
+    Key,
+    FirstKey, // a key or `}`
+    Colon,
+    CommaOrClose,
+}
+
+// Rejects the commas and colons which the tree would silently repair:
+// missing, doubled, leading or trailing ones, and colons outside of
+// objects.  Brackets are already known to balance.
+fn check_structure(minimized: &str) -> Result<(), JsonxfError> {
+    let mut objects: Vec<bool> = vec![]; // is each open bracket a `{`?
+    let mut expect = Expect::Value;
+    let mut key = "";
+    let mut result = Ok(());
+    let _ = tokenize(minimized, |token, range| {
+        let text = &minimized[range];
+        let in_object = objects.last() == Some(&true);
+        let next = match (token, expect) {
+            (Token::ArrayStart, Expect::Value) | (Token::ArrayStart, Expect::FirstValue) => {
+                objects.push(false);
+                Some(Expect::FirstValue)
+            }
+            (Token::ObjectStart, Expect::Value) | (Token::ObjectStart, Expect::FirstValue) => {
+                objects.push(true);
+                Some(Expect::FirstKey)
+            }
+            (Token::ArrayEnd, Expect::FirstValue)
+            | (Token::ArrayEnd, Expect::CommaOrClose)
+            | (Token::ObjectEnd, Expect::FirstKey)
+            | (Token::ObjectEnd, Expect::CommaOrClose) => {
+                objects.pop();
+                Some(after_value(&objects))
+            }
+            (Token::Comma, Expect::CommaOrClose) if !objects.is_empty() => Some(if in_object {
+                Expect::Key
+            } else {
+                Expect::Value
+            }),
+            (Token::Key(_), Expect::Key)
+            | (Token::Key(_), Expect::FirstKey)
+            | (Token::ValueString(_), Expect::Key)
+            | (Token::ValueString(_), Expect::FirstKey) => {
+                key = text;
+                Some(Expect::Colon)
+            }
+            (Token::Colon, Expect::Colon) => Some(Expect::Value),
+            (_, Expect::Colon) => {
+                let name = decode_string(key.as_bytes()).unwrap_or_else(|| String::from(key));
+                result = Err(error(format!("key {:?} has no value", name)));
+                return ControlFlow::Break(());
+            }
+            (Token::Key(_), Expect::Value)
+            | (Token::Key(_), Expect::FirstValue)
+            | (Token::ValueString(_), Expect::Value)
+            | (Token::ValueString(_), Expect::FirstValue)
+            | (Token::ValueNumber(_), Expect::Value)
+            | (Token::ValueNumber(_), Expect::FirstValue)
+            | (Token::ValueLiteral(_), Expect::Value)
+            | (Token::ValueLiteral(_), Expect::FirstValue) => Some(after_value(&objects)),
+            _ => None,
+        };
+        match next {
+            Some(next) => {
+                expect = next;
+                ControlFlow::Continue(())
+            }
+            None => {
+                result = Err(error(format!("unexpected `{}`", text)));
+                ControlFlow::Break(())
+            }
+        }
+    });
+    result
+}
+
+// Another top-level value may follow one; they are counted when the
+// tree is built.
+fn after_value(objects: &[bool]) -> Expect {
+    if objects.is_empty() {
+        Expect::Value
+    } else {
+        Expect::CommaOrClose
+    }
+}
+
+fn write_scalar(bytes: &[u8], output: &mut String) -> Result<(), JsonxfError> {
+    match bytes.first() {
+        Some(b'"') => match decode_string(bytes) {
+            Some(s) => write_string(&s, output),
+            None => return Err(invalid("string", bytes)),
+        },
+        Some(&b) if b == b'-' || b.is_ascii_digit() => write_number(bytes, output)?,
+        _ => match bytes {
+            b"true" | b"false" | b"null" => output.push_str(std::str::from_utf8(bytes).unwrap()),
+            _ => return Err(invalid("literal", bytes)),
+        },
+    }
+    Ok(())
+}
+
+// Decodes a quoted string, or returns None if it is unterminated or
+// has an escape which does not decode to Unicode text.
+fn decode_string(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < 2 || bytes[0] != b'"' || bytes[bytes.len() - 1] != b'"' {
+        return None;
+    }
+    let contents = &bytes[1..bytes.len() - 1];
+    let trailing_backslashes = contents.iter().rev().take_while(|&&b| b == b'\\').count();
+    if trailing_backslashes % 2 == 1 {
+        return None;
+    }
+    unescape(contents)
+}
+
+fn write_string(s: &str, output: &mut String) {
+    output.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\u{8}' => output.push_str("\\b"),
+            '\t' => output.push_str("\\t"),
+            '\n' => output.push_str("\\n"),
+            '\u{c}' => output.push_str("\\f"),
+            '\r' => output.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+// Writes a number the way ECMAScript's Number.prototype.toString()
+// does, from the shortest digits which round-trip to the same double.
+fn write_number(bytes: &[u8], output: &mut String) -> Result<(), JsonxfError> {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) if is_json_number(bytes) => text,
+        _ => return Err(invalid("number", bytes)),
+    };
+    let value: f64 = match text.parse() {
+        Ok(value) if f64::is_finite(value) => value,
+        _ => return Err(invalid("number", bytes)),
+    };
+    if value == 0.0 {
+        output.push('0');
+        return Ok(());
+    }
+    let value = if value < 0.0 {
+        output.push('-');
+        -value
+    } else {
+        value
+    };
+
+    // `{:e}` gives the shortest round-tripping digits, as in `1.25e-7`
+    let exp_form = format!("{:e}", value);
+    let (mantissa, exp) = exp_form.split_at(exp_form.find('e').unwrap());
+    let mut digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exp[1..].parse::<i32>().unwrap() + 1;
+    if digits.ends_with(|c: char| (c as u8) % 2 == 1) {
+        break_tie_to_even(value, &mut digits, n - k);
+    }
+
+    if k <= n && n <= 21 {
+        output.push_str(&digits);
+        output.extend((k..n).map(|_| '0'));
+    } else if 0 < n && n <= 21 {
+        output.push_str(&digits[..n as usize]);
+        output.push('.');
+        output.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        output.push_str("0.");
+        output.extend((n..0).map(|_| '0'));
+        output.push_str(&digits);
+    } else {
+        output.push_str(&digits[..1]);
+        if k > 1 {
+            output.push('.');
+            output.push_str(&digits[1..]);
+        }
+        let _ = write!(
+            output,
+            "e{}{}",
+            if n > 0 { "+" } else { "-" },
+            (n - 1).abs()
+        );
+    }
+    Ok(())
+}
+
+// When `value` lies exactly halfway between two numbers with as many
+// digits as the shortest form, and both round-trip, ECMAScript picks
+// the one whose last digit is even.  Rust picks either, so `digits`,
+// which ends in an odd digit and is scaled by `10^scale`, may need to
+// move to its neighbour.
+fn break_tie_to_even(value: f64, digits: &mut String, scale: i32) {
+    // Every double has an exact decimal form of at most 767 digits
+    let exact_form = format!("{:.800e}", value);
+    let exact: String = exact_form[..exact_form.find('e').unwrap()]
+        .chars()
+        .filter(|&c| c != '.')
+        .collect();
+    let k = digits.len();
+    if !exact[k..].starts_with('5') || exact[k + 1..].bytes().any(|b| b != b'0') {
+        return;
+    }
+    let last = digits.as_bytes()[k - 1];
+    let neighbour = if exact[..k] == digits[..] {
+        if last == b'9' {
+            return;
+        }
+        last + 1
+    } else {
+        last - 1
+    };
+    let mut candidate = String::from(&digits[..k - 1]);
+    candidate.push(neighbour as char);
+    if format!("{}e{}", candidate, scale).parse::<f64>() == Ok(value) {
+        *digits = candidate;
+    }
+}
+
+fn invalid(what: &str, bytes: &[u8]) -> JsonxfError {
+    error(format!(
+        "invalid {} `{}`",
+        what,
+        String::from_utf8_lossy(bytes)
+    ))
+}
+
+fn error(reason: String) -> JsonxfError {
+    JsonxfError::NotCanonicalizable { reason }
+}
@@ -14,18 +14,74 @@
 //! but no guarantees are offered around the detection and rejection
 //! of invalid input.
 //!
+//! Everything but the stream functions also works without the
+//! standard library: disable the default `std` feature, and write
+//! output to anything implementing `jsonxf::io::Write`.
+//!
 //! GitHub:
 //! <a href="https://github.com/gamache/jsonxf" target="_blank">gamache/jsonxf</a>
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use std::fmt;
+#[cfg(feature = "std")]
 use std::io::prelude::*;
+#[cfg(feature = "std")]
 use std::io::BufReader;
+#[cfg(feature = "std")]
 use std::io::BufWriter;
-use std::io::Error;
+#[cfg(feature = "std")]
 use std::io::ErrorKind;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "std")]
+mod chunks;
+mod error;
+#[cfg(not(feature = "std"))]
+pub mod io;
+mod jcs;
+mod stats;
+mod toggle;
+mod tokens;
+mod tree;
+mod utf16;
+#[cfg(feature = "std")]
+mod writer;
 
+#[cfg(feature = "std")]
+pub use chunks::FormattedChunks;
+pub use error::JsonxfError;
+#[cfg(not(feature = "std"))]
+use io::Write;
+pub use jcs::canonicalize_jcs;
+use stats::LimitedWriter;
+pub use stats::Stats;
+#[cfg(feature = "std")]
+use stats::{CountingReader, CountingWriter, ProgressReader};
+pub use toggle::{detect_indent, is_minimized, looks_pretty, reformat, Toggle};
+use toggle::{sample_indent, IndentSample};
+pub use tokens::{tokenize, Token};
+use tree::Tree;
+use utf16::Utf16Decoder;
+#[cfg(feature = "std")]
+pub use writer::PrettyWriter;
+
+#[cfg(feature = "std")]
 const BUF_SIZE: usize = 1024 * 16;
 
+// How much input `detect_indent` looks through before giving up.
+const MAX_INDENT_SAMPLE: usize = 1024 * 64;
+
 const C_CR: u8 = b'\r';
 const C_LF: u8 = b'\n';
 const C_TAB: u8 = b'\t';
@@ -34,7 +90,12 @@ const C_SPACE: u8 = b' ';
 const C_COMMA: u8 = b',';
 const C_COLON: u8 = b':';
 const C_QUOTE: u8 = b'"';
+const C_APOSTROPHE: u8 = b'\'';
 const C_BACKSLASH: u8 = b'\\';
+const C_SLASH: u8 = b'/';
+const C_STAR: u8 = b'*';
+
+const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
 
 const C_LEFT_BRACE: u8 = b'{';
 const C_LEFT_BRACKET: u8 = b'[';
@@ -55,308 +116,3130 @@ const C_RIGHT_BRACKET: u8 = b']';
 ///     "{\r\n  \"a\": 1\r\n}"
 /// );
 /// ```
+#[derive(Clone)]
 pub struct Formatter {
     /// Used for beginning-of-line indentation in arrays and objects.
+    /// [`Formatter::indent_spaces`] and [`Formatter::indent_tabs`] set
+    /// the common ones.  Deep indentation is built up once and reused, so
+    /// there is no cost to a long string here.
     pub indent: String,
 
+    /// Used for beginning-of-line indentation instead of `indent`, if
+    /// not empty: the entry at index `n` for lines at nesting depth
+    /// `n`, so the opening and closing lines of a top-level array or
+    /// object both start with the first entry.  Lines nested more
+    /// deeply than the list goes use its last entry plus one `indent`
+    /// for each extra level.
+    pub depth_indents: Vec<String>,
+
+    /// Indent with the indentation of the input instead of `indent`,
+    /// as found by [`detect_indent`], so that reformatting keeps a
+    /// file's style.  Input which isn't indented, such as minimized
+    /// input, is indented with `indent`.  No output is written until
+    /// the start of the input has shown which it is.  This has no
+    /// effect on output without line separators.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.detect_indent = true;
+    /// assert_eq!(
+    ///     fmt.format("{\n\t\"a\": [1, 2]}").unwrap(),
+    ///     "{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t]\n}"
+    /// );
+    /// assert_eq!(fmt.format("{\"a\":1}").unwrap(), "{\n  \"a\": 1\n}");
+    /// ```
+    pub detect_indent: bool,
+
+    /// Used instead of `indent` for the levels of indentation added by
+    /// arrays, if set.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.array_indent = Some(String::from("  "));
+    /// fmt.object_indent = Some(String::from("    "));
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":[1]}").unwrap(),
+    ///     "{\n    \"a\": [\n      1\n    ]\n}"
+    /// );
+    /// ```
+    pub array_indent: Option<String>,
+
+    /// Used instead of `indent` for the levels of indentation added by
+    /// objects, if set.
+    pub object_indent: Option<String>,
+
+    /// Indent everything this many levels more than usual, including
+    /// the first and last lines of each top-level value, as when
+    /// embedding the output in another indented document.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.base_indent_level = 1;
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":[]}").unwrap(),
+    ///     "  {\n    \"a\": []\n  }"
+    /// );
+    /// ```
+    pub base_indent_level: usize,
+
+    /// Indent lines by at most this many levels, however deeply they
+    /// are nested, so that lines don't march off the right edge.
+    /// Closing brackets are indented like their opening lines.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.max_indent_level = Some(1);
+    /// assert_eq!(
+    ///     fmt.format("[[[1]]]").unwrap(),
+    ///     "[\n  [\n  [\n  1\n  ]\n  ]\n]"
+    /// );
+    /// ```
+    pub max_indent_level: Option<usize>,
+
+    /// Break arrays and objects across lines only this many levels
+    /// deep, and write the ones nested deeper minimized on one line,
+    /// for a preview of a large document.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.expand_to_depth = Some(1);
+    /// assert_eq!(
+    ///     fmt.format("{\"a\": {\"b\": [1, 2]}, \"c\": 3}").unwrap(),
+    ///     "{\n  \"a\": {\"b\":[1,2]},\n  \"c\": 3\n}"
+    /// );
+    /// ```
+    pub expand_to_depth: Option<usize>,
+
     /// Used inside arrays and objects.
     pub line_separator: String,
 
-    /// Used between root-level arrays and objects.
+    /// Used between root-level values.
     pub record_separator: String,
 
+    /// Used before a colon inside objects.
+    pub before_colon: String,
+
     /// Used after a colon inside objects.
     pub after_colon: String,
 
+    /// Used after an opening bracket and before a closing one, in
+    /// arrays and objects which aren't empty and are written on one
+    /// line.  It is left out where `line_separator` breaks the line.
+    pub bracket_padding: String,
+
+    /// Used instead of `bracket_padding` after the `[` of an array
+    /// which isn't empty, if set.  Like `bracket_padding`, it is only
+    /// used on arrays written on one line.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::spaced_minimizer();
+    /// fmt.after_array_open = Some(String::from(" "));
+    /// fmt.before_array_close = Some(String::from(" "));
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":[1,2]}").unwrap(),
+    ///     "{\"a\": [ 1, 2 ]}"
+    /// );
+    /// ```
+    pub after_array_open: Option<String>,
+
+    /// Used instead of `bracket_padding` before the `]` of an array
+    /// which isn't empty, if set.
+    pub before_array_close: Option<String>,
+
+    /// Used instead of `bracket_padding` after the `{` of an object
+    /// which isn't empty, if set.
+    pub after_object_open: Option<String>,
+
+    /// Used instead of `bracket_padding` before the `}` of an object
+    /// which isn't empty, if set.
+    pub before_object_close: Option<String>,
+
+    /// Used after a comma inside arrays and objects.  Along with
+    /// `before_colon` and `after_colon`, this gives the control of
+    /// Python's `separators=(item_separator, key_separator)`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// // Like json.dumps(value, separators=(", ", ":"))
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.after_comma = String::from(" ");
+    /// assert_eq!(
+    ///     fmt.format("{\"a\": [1, 2], \"b\": 3}").unwrap(),
+    ///     "{\"a\":[1, 2], \"b\":3}"
+    /// );
+    /// ```
+    pub after_comma: String,
+
     /// Used at very end of output.
     pub trailing_output: String,
 
     /// Add a record_separator as soon as a record ends, before seeing a
     /// subsequent record. Useful when there's a long time between records.
+    /// The output then ends with a record_separator too, which is how
+    /// the command line tool ends its output with a newline unless
+    /// given `-n`/`--no-trailing-newline`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// assert_eq!(fmt.format("[1]").unwrap(), "[\n  1\n]");
+    /// fmt.eager_record_separators = true;
+    /// assert_eq!(fmt.format("[1]").unwrap(), "[\n  1\n]\n");
+    /// ```
     pub eager_record_separators: bool,
 
-    // private mutable state
-    depth: usize,       // current nesting depth
-    in_string: bool,    // is the next byte part of a string?
-    in_backslash: bool, // does the next byte follow a backslash in a string?
-    empty: bool,        // is the next byte in an empty object or array?
-    first: bool,        // is this the first byte of input?
-}
+    /// Flush the output as soon as each top-level value ends, so that
+    /// whatever reads it sees each record straight away even when the
+    /// input arrives slowly, as from `tail -f`.  Options which hold the
+    /// whole input in memory, like `sort_keys`, only write it at the
+    /// end.
+    pub flush_after_documents: bool,
 
-impl Formatter {
-    fn default() -> Formatter {
-        Formatter {
-            indent: String::from("  "),
-            line_separator: String::from("\n"),
-            record_separator: String::from("\n"),
-            after_colon: String::from(" "),
-            trailing_output: String::from(""),
-            eager_record_separators: false,
-            depth: 0,
-            in_string: false,
-            in_backslash: false,
-            empty: false,
-            first: true,
-        }
-    }
+    /// What to do with `// line` and `/* block */` comments found
+    /// outside of strings, as allowed in JSONC files.  Defaults to
+    /// `CommentMode::Unrecognized`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use jsonxf::CommentMode;
+    ///
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.comment_mode = CommentMode::Preserve;
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":1, // one\n/* two */\n\"b\":2}").unwrap(),
+    ///     "{\n  \"a\": 1, // one\n  /* two */\n  \"b\": 2\n}"
+    /// );
+    ///
+    /// fmt.comment_mode = CommentMode::Reject;
+    /// assert!(fmt.format("[1 /* two */]").is_err());
+    /// ```
+    pub comment_mode: CommentMode,
 
-    /// Returns a Formatter set up for pretty-printing.
-    /// Defaults to using two spaces of indentation,
-    /// Unix newlines, and no whitespace at EOF.
+    /// Drop commas which are followed only by whitespace and a closing
+    /// bracket, as in `[1, 2,]`.  Runs of commas are collapsed to one.
+    pub remove_trailing_commas: bool,
+
+    /// Add a comma after the last element of every array and object
+    /// which is broken across lines, so that appending an element
+    /// changes only one line in a diff.  Trailing commas in the input
+    /// are dropped first, as with `remove_trailing_commas`.  The
+    /// output is JSON5, not JSON, and most JSON parsers will reject it.
     ///
     /// # Example:
     ///
     /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.add_trailing_commas = true;
     /// assert_eq!(
-    ///     jsonxf::Formatter::pretty_printer().format("{\"a\":1}").unwrap(),
-    ///     "{\n  \"a\": 1\n}"
+    ///     fmt.format("{\"a\":[1,2,],\"b\":[]}").unwrap(),
+    ///     "{\n  \"a\": [\n    1,\n    2,\n  ],\n  \"b\": [],\n}"
     /// );
     /// ```
-    pub fn pretty_printer() -> Formatter {
-        Formatter::default()
-    }
+    pub add_trailing_commas: bool,
 
-    /// Returns a Formatter set up for minimizing.
-    /// Defaults to using Unix newlines between records,
-    /// and no whitespace at EOF.
+    /// How empty arrays and objects are written.  Defaults to
+    /// `EmptyStyle::Compact`.
+    pub empty_style: EmptyStyle,
+
+    /// Return an error, rather than formatting on a best-effort basis,
+    /// when a closing bracket has no matching opening bracket, closes
+    /// the wrong kind of bracket, or the input ends inside a string,
+    /// array or object.  Unescaped control characters and invalid
+    /// UTF-8 inside strings are reported too, except for control
+    /// characters which `canonicalize_string_whitespace` escapes.
+    pub strict: bool,
+
+    /// Return an error when arrays and objects are nested more deeply
+    /// than this.  Defaults to `None`, meaning unlimited.
+    pub max_depth: Option<usize>,
+
+    /// Return an error if anything but whitespace follows the first
+    /// value, rather than formatting each value as a separate record.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.single_document = true;
+    /// assert_eq!(fmt.format("{\"a\": 1}\n").unwrap(), "{\"a\":1}");
+    /// assert!(fmt.format("{\"a\": 1} garbage").is_err());
+    /// ```
+    pub single_document: bool,
+
+    /// Return an error if the first byte of input which isn't
+    /// whitespace can't begin a value: anything but a bracket, a quote,
+    /// a digit, `-`, or the first letter of `true`, `false` or `null`.
+    /// This catches framing bytes or other junk ahead of the JSON.
     ///
     /// # Example:
     ///
     /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.reject_leading_junk = true;
+    /// assert_eq!(fmt.format(" [1]").unwrap(), "[1]");
     /// assert_eq!(
-    ///     jsonxf::Formatter::minimizer().format("{  \"a\" : 1  }\n").unwrap(),
-    ///     "{\"a\":1}"
+    ///     fmt.format("xx{\"a\":1}").unwrap_err().to_string(),
+    ///     "unexpected byte 0x78 before the first value at byte 0"
     /// );
     /// ```
-    pub fn minimizer() -> Formatter {
-        let mut xf = Formatter::default();
-        xf.indent = String::from("");
-        xf.line_separator = String::from("");
-        xf.record_separator = String::from("\n");
-        xf.after_colon = String::from("");
-        xf
-    }
+    pub reject_leading_junk: bool,
 
-    /// Formats a string of JSON-encoded data.
+    /// Return an error once the output grows past this many bytes,
+    /// counting everything written, whitespace included.  Output is
+    /// cut off at exactly this length, and formatting stops there
+    /// without reading the rest of the input.  Defaults to `None`,
+    /// meaning unlimited.
     ///
-    /// Input must be valid JSON data in UTF-8 encoding.
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.max_output_bytes = Some(8);
+    /// assert!(fmt.format("[1,2,3]").is_err());
+    /// ```
+    pub max_output_bytes: Option<usize>,
+
+    /// Output the members of every object sorted by key, comparing
+    /// the keys' bytes.  Array elements keep their order.
+    ///
+    /// This can't be done in a single streaming pass, so the whole
+    /// input is held in memory and output is only written once the
+    /// input is finished.
+    pub sort_keys: bool,
+
+    /// Replace every non-ASCII character inside strings with a `\uXXXX`
+    /// escape, using surrogate pairs where needed.  Existing escapes
+    /// are left as-is.
+    pub ascii_only: bool,
+
+    /// Write an array or object on a single line, as in `[1, 2, 3]`,
+    /// when that takes at most this many characters.  Arrays and
+    /// objects which don't fit are broken across lines as usual, but
+    /// may still contain ones which fit.
+    ///
+    /// Like `sort_keys`, this holds the whole input in memory.
+    pub max_inline_length: Option<usize>,
+
+    /// Write an array or object on a single line when the whole line,
+    /// including its indentation, key, and any comma after it, takes
+    /// at most this many characters.  Others are broken across lines,
+    /// and each of their elements gets the same chance, so only as
+    /// much is broken up as is needed.  The indentation is measured
+    /// from `indent`, `array_indent` and `object_indent`.
+    ///
+    /// Like `sort_keys`, this holds the whole input in memory.
     ///
     /// # Example:
     ///
     /// ```
     /// let mut fmt = jsonxf::Formatter::pretty_printer();
-    /// fmt.indent = String::from("\t");
-    /// fmt.trailing_output = String::from("\n");
+    /// fmt.wrap_width = Some(20);
     /// assert_eq!(
-    ///     fmt.format("{\"a\":1}").unwrap(),
-    ///     "{\n\t\"a\": 1\n}\n"
+    ///     fmt.format("{\"a\":[1,2,3],\"b\":{\"c\":[4,5,6]}}").unwrap(),
+    ///     "{\n  \"a\": [1, 2, 3],\n  \"b\": {\n    \"c\": [4, 5, 6]\n  }\n}"
     /// );
     /// ```
-    pub fn format(&mut self, json_string: &str) -> Result<String, String> {
-        let mut input = json_string.as_bytes();
-        let mut output: Vec<u8> = vec![];
-        match self.format_stream(&mut input, &mut output) {
-            Ok(_) => {}
-            Err(f) => {
-                return Err(f.to_string());
-            }
-        };
-        let output_string = match String::from_utf8(output) {
-            Ok(s) => s,
-            Err(f) => {
-                return Err(f.to_string());
-            }
-        };
-        Ok(output_string)
-    }
+    pub wrap_width: Option<usize>,
 
-    /// Formats a stream of JSON-encoded data.
+    /// Pad the keys of each object with spaces so that its colons and
+    /// values line up in a column, as in configuration files.  Each
+    /// object is aligned separately, and objects written on one line
+    /// are left alone.  Has no effect on minimized output.
     ///
-    /// Input must be valid JSON data in UTF-8 encoding.
+    /// Like `sort_keys`, this holds the whole input in memory.
     ///
     /// # Example:
     ///
-    /// ```no_run
+    /// ```
     /// let mut fmt = jsonxf::Formatter::pretty_printer();
-    /// fmt.indent = String::from("\t");
-    /// fmt.trailing_output = String::from("\n");
-    /// match fmt.format_stream(&mut std::io::stdin(), &mut std::io::stdout()) {
-    ///     Ok(_) => { /* YAY */ },
-    ///     Err(e) => { panic!(e.to_string()); }
-    /// }
+    /// fmt.align_values = true;
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":1,\"bcd\":2}").unwrap(),
+    ///     "{\n  \"a\"  : 1,\n  \"bcd\": 2\n}"
+    /// );
     /// ```
-    pub fn format_stream(
-        &mut self,
-        input: &mut dyn Read,
-        output: &mut dyn Write,
-    ) -> Result<(), Error> {
-        let mut reader = BufReader::new(input);
-        let mut writer = BufWriter::new(output);
-        self.format_stream_unbuffered(&mut reader, &mut writer)
-    }
+    pub align_values: bool,
 
-    /// Formats a stream of JSON-encoded data without buffering.
+    /// Rewrite numbers in a canonical form: no leading `+`, no trailing
+    /// zeros after the decimal point, and a lowercase exponent without
+    /// `+` or leading zeros.  Anything which doesn't parse as a number
+    /// is left alone.
+    pub normalize_numbers: bool,
+
+    /// Return `JsonxfError::InvalidNumber` for a number which doesn't
+    /// follow the JSON grammar, such as `1.2.3`, `--5`, `1e` or `01`.
+    /// Anything starting with `-`, `+`, `.` or a digit is taken for a
+    /// number.  Valid numbers are written exactly as they were given,
+    /// unless `normalize_numbers` is set too.
     ///
-    /// This will perform many small writes, so it's advisable to use an
-    /// output that does its own buffering. In simple cases, use
-    /// [`Formatter::format_stream`] instead.
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.validate_numbers = true;
+    /// assert_eq!(fmt.format("[1.50, -0e+00]").unwrap(), "[1.50,-0e+00]");
+    /// assert!(fmt.format("[1, 1.2.3]").is_err());
+    /// ```
+    pub validate_numbers: bool,
+
+    /// Write `NaN`, `Infinity` and `-Infinity`, which JavaScript
+    /// produces but JSON doesn't allow, as `null`.
     ///
     /// # Example:
     ///
-    /// ```no_run
-    /// let mut fmt = jsonxf::Formatter::pretty_printer();
-    /// let mut stdin = std::io::stdin();
-    /// let mut stdout = std::io::stdout();
-    /// fmt.format_stream_unbuffered(&mut stdin, &mut std::io::LineWriter::new(stdout))
-    ///     .unwrap();
     /// ```
-    pub fn format_stream_unbuffered(
-        &mut self,
-        input: &mut impl Read,
-        output: &mut impl Write,
-    ) -> Result<(), Error> {
-        let mut buf = [0_u8; BUF_SIZE];
-        loop {
-            match input.read(&mut buf) {
-                Ok(0) => {
-                    break;
-                }
-                Ok(n) => {
-                    self.format_buf(&buf[0..n], output)?;
-                }
-                Err(e) if e.kind() == ErrorKind::Interrupted => {
-                    continue;
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-        }
-        output.write_all(self.trailing_output.as_bytes())?;
-        Ok(())
-    }
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.nonfinite_to_null = true;
+    /// assert_eq!(
+    ///     fmt.format("[NaN, -Infinity, 1]").unwrap(),
+    ///     "[null,null,1]"
+    /// );
+    /// ```
+    pub nonfinite_to_null: bool,
 
-    /// Format directly from a buffer into a writer.
+    /// Decode `\uXXXX` escapes inside strings, including surrogate
+    /// pairs, into UTF-8.  Escapes which are still required (quotes,
+    /// backslashes and control characters) are kept, as are invalid
+    /// escapes and lone surrogates.  With `ascii_only`, escapes of
+    /// non-ASCII characters are kept too.
+    pub unescape_unicode: bool,
+
+    /// Escape every `/` inside strings as `\/`, as is sometimes needed
+    /// when JSON is embedded in HTML.  Slashes which are already
+    /// escaped are left alone.
     ///
-    /// This may be called on chunks of a JSON document to format it bit by bit.
+    /// # Example:
     ///
-    /// As such, it does not add the `trailing_output` at the end.
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.escape_slashes = true;
+    /// assert_eq!(
+    ///     fmt.format(r#"["</script>", "a\/b"]"#).unwrap(),
+    ///     r#"["<\/script>","a\/b"]"#
+    /// );
+    /// ```
+    pub escape_slashes: bool,
+
+    /// Escape every `<`, `>` and `&` inside strings as `\u003c`,
+    /// `\u003e` and `\u0026`, so that the output can go inside an HTML
+    /// `<script>` element, as Go's `encoding/json` does.
     ///
     /// # Example:
     ///
-    /// ```no_run
-    /// let text = "[1, 2, 3]";
-    /// let mut fmt = jsonxf::Formatter::pretty_printer();
-    /// let mut stdout = std::io::stdout();
-    /// fmt.format_buf(text.as_bytes(), &mut stdout).unwrap();
     /// ```
-    pub fn format_buf(&mut self, buf: &[u8], writer: &mut impl Write) -> Result<(), Error> {
-        let mut n = 0;
-        while n < buf.len() {
-            let b = buf[n];
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.escape_html = true;
+    /// assert_eq!(
+    ///     fmt.format(r#"["</script> & more", 1]"#).unwrap(),
+    ///     r#"["\u003c/script\u003e \u0026 more",1]"#
+    /// );
+    /// ```
+    pub escape_html: bool,
 
-            if self.in_string {
-                if self.in_backslash {
-                    writer.write_all(&buf[n..n + 1])?;
-                    self.in_backslash = false;
-                } else {
-                    match memchr::memchr2(C_QUOTE, C_BACKSLASH, &buf[n..]) {
-                        None => {
-                            // The whole rest of buf is part of the string
-                            writer.write_all(&buf[n..])?;
-                            break;
-                        }
-                        Some(index) => {
-                            let length = index + 1;
-                            writer.write_all(&buf[n..n + length])?;
-                            if buf[n + index] == C_QUOTE {
-                                // End of string
-                                self.in_string = false;
-                            } else {
-                                // Backslash
-                                self.in_backslash = true;
-                            }
-                            n += length;
-                            continue;
-                        }
+    /// Remove escapes inside strings which aren't needed: `\/` becomes
+    /// `/`, and `\uXXXX` escapes are decoded as with
+    /// `unescape_unicode`.  Quotes, backslashes and control characters
+    /// stay escaped.  With `escape_slashes`, slashes stay escaped too.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.remove_needless_escapes = true;
+    /// assert_eq!(
+    ///     fmt.format(r#""a\/b \u0041 \ud83d\ude00 \n \u0022""#).unwrap(),
+    ///     "\"a/b A \u{1f600} \\n \\u0022\""
+    /// );
+    /// ```
+    pub remove_needless_escapes: bool,
+
+    /// The case of the four hex digits in `\uXXXX` escapes inside
+    /// strings, including those written by `ascii_only` and
+    /// `canonicalize_string_whitespace`.  Escapes are not decoded, and
+    /// other escapes are left alone.  Defaults to `HexCase::Keep`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.hex_case = jsonxf::HexCase::Lower;
+    /// assert_eq!(
+    ///     fmt.format(r#""\u00E9 \uD83D\uDE00 \n \uAb""#).unwrap(),
+    ///     r#""\u00e9 \ud83d\ude00 \n \uAb""#
+    /// );
+    /// ```
+    pub hex_case: HexCase,
+
+    /// Replace control characters inside strings, such as literal tabs
+    /// and newlines, with their shortest escape, as JSON requires.
+    /// Characters which are already escaped are left alone.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.canonicalize_string_whitespace = true;
+    /// assert_eq!(
+    ///     fmt.format("\"a\tb\nc\u{1}\"").unwrap(),
+    ///     r#""a\tb\nc\u0001""#
+    /// );
+    /// ```
+    pub canonicalize_string_whitespace: bool,
+
+    /// What to do with object members whose keys are the same as
+    /// another member's.  Defaults to `DuplicateKeys::Keep`.
+    ///
+    /// Apart from `Keep`, this holds the whole input in memory, like
+    /// `sort_keys`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.duplicate_keys = jsonxf::DuplicateKeys::Last;
+    /// assert_eq!(
+    ///     fmt.format("{\"a\": 1, \"b\": 2, \"a\": 3}").unwrap(),
+    ///     "{\"b\":2,\"a\":3}"
+    /// );
+    /// ```
+    pub duplicate_keys: DuplicateKeys,
+
+    /// Replace string values of object members with these keys by
+    /// `"***"`, so that documents holding secrets can be logged.  Keys
+    /// are compared after decoding their escapes, at any depth.
+    ///
+    /// If this isn't empty, it holds the whole input in memory, like
+    /// `sort_keys`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.redact_keys = vec![String::from("password")];
+    /// assert_eq!(
+    ///     fmt.format("{\"user\": \"ann\", \"password\": \"hunter2\", \"pin\": 1234}").unwrap(),
+    ///     "{\"user\":\"ann\",\"password\":\"***\",\"pin\":1234}"
+    /// );
+    /// ```
+    pub redact_keys: Vec<String>,
+
+    /// With `redact_keys`, replace values of any type by `"***"`,
+    /// including whole arrays and objects, not just strings.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.redact_keys = vec![String::from("pin"), String::from("token")];
+    /// fmt.redact_non_strings = true;
+    /// assert_eq!(
+    ///     fmt.format("{\"pin\": 1234, \"token\": {\"id\": 5}}").unwrap(),
+    ///     "{\"pin\":\"***\",\"token\":\"***\"}"
+    /// );
+    /// ```
+    pub redact_non_strings: bool,
+
+    /// What [`Formatter::format_ndjson_stream`] does with blank lines.
+    /// Defaults to `BlankLines::Skip`.
+    pub blank_lines: BlankLines,
+
+    /// Wrap keys, strings, numbers, literals and punctuation in ANSI
+    /// color codes, for output to a terminal.  Defaults to `None`,
+    /// meaning no color.
+    pub colors: Option<ColorScheme>,
+
+    /// Copy a UTF-8 byte order mark at the start of input to the
+    /// output.  By default it is discarded.
+    pub preserve_bom: bool,
+
+    /// Transcode input which starts with a UTF-16 byte order mark,
+    /// little- or big-endian, to UTF-8 before formatting it.  Output
+    /// is always UTF-8, and its byte order mark is kept only with
+    /// `preserve_bom`.  Unpaired surrogates become U+FFFD, and byte
+    /// offsets in errors count the UTF-8 bytes.  Input without the
+    /// mark is formatted as usual.  Not used by
+    /// [`Formatter::format_ndjson_stream`], which splits lines first.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.decode_utf16 = true;
+    /// let input = b"\xff\xfe[\x00 \x00\"\x00\xe9\x00\"\x00]\x00";
+    /// assert_eq!(fmt.format_bytes(input).unwrap(), "[\"\u{e9}\"]".as_bytes());
+    /// ```
+    pub decode_utf16: bool,
+
+    /// Accept strings in single quotes, as JSON5 allows, and write them
+    /// in double quotes.  Double quotes inside them are escaped, and
+    /// `\'` escapes are replaced by plain single quotes.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.single_quotes = true;
+    /// assert_eq!(
+    ///     fmt.format("{'a': 'say \"it\\'s\"'}").unwrap(),
+    ///     "{\"a\":\"say \\\"it's\\\"\"}"
+    /// );
+    /// ```
+    pub single_quotes: bool,
+
+    /// Put double quotes around object keys written without them, as
+    /// JSON5 and JavaScript allow.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.quote_keys = true;
+    /// assert_eq!(
+    ///     fmt.format("{a: 1, $b_2: {c: true}}").unwrap(),
+    ///     "{\"a\":1,\"$b_2\":{\"c\":true}}"
+    /// );
+    /// ```
+    pub quote_keys: bool,
+
+    /// Size in bytes of the buffers which [`Formatter::format_stream`]
+    /// and similar functions read and write through, and of the chunks
+    /// in which they and [`FormattedChunks`] read input.  Defaults to
+    /// 8 KiB; larger buffers can help with big files on fast storage.
+    pub buffer_capacity: usize,
+
+    // private mutable state
+    depth: usize,        // current nesting depth
+    in_string: bool,     // is the next byte part of a string?
+    in_backslash: bool,  // does the next byte follow a backslash in a string?
+    empty: bool,         // is the next byte in an empty object or array?
+    first: bool,         // is this the first byte of input?
+    in_scalar: bool,     // is the previous byte part of a number or literal?
+    after_scalar: bool,  // has a number or literal ended since the last punctuation?
+    comment: Comment,    // are we inside (or about to start) a comment?
+    pending_comma: bool, // has a comma been held back?
+    offset: usize,       // number of input bytes before the current buffer
+    buffer: Vec<u8>,     // input held back for sort_keys and similar options
+    utf8_buf: [u8; 4],   // the current multi-byte character in a string
+    utf8_len: usize,     // how many bytes of it have been seen
+    utf8_need: usize,    // how many bytes long it is
+    utf8_start: usize,   // offset of its first byte
+
+    escape_buf: [u8; 12],        // the current `\uXXXX` escape, or pair of them
+    escape_len: usize,           // how many bytes of it have been seen
+    bom_checked: bool,           // have we looked for a byte order mark?
+    indent_checked: bool,        // have we looked for the input's indentation?
+    indent_sample: Vec<u8>,      // input held back until then
+    seen_indent: Option<String>, // the input's indentation, used instead of indent
+    utf16: Utf16Decoder,         // for decode_utf16
+    inline_starts: Vec<usize>,   // offsets of brackets to write on one line
+    inline_next: usize,          // index of the next one to look for
+    inline_depth: Option<usize>, // depth outside the current one-line bracket
+    inline_minimized: bool,      // is that bracket below expand_to_depth?
+    colon_pads: Vec<ColonPad>,   // offsets of colons to pad, and by how much
+    colon_next: usize,           // index of the next one to look for
+    structure: Vec<u8>,          // open brackets, innermost last
+    expect_key: bool,            // would a string here be an object key?
+    stats: Stats,                // counts of structures and strings so far
+    scalar_buf: Vec<u8>,         // the current number, for normalize_numbers and the like
+    string_start: usize,         // offset of the current string's opening quote
+    scalar_start: usize,         // offset of the current number or literal
+    string_chars: usize,         // characters in the current string so far
+    in_key: bool,                // is the current string or scalar an object key?
+    quote: u8,                   // the quote the current string started with
+    indent_buf: Vec<u8>,         // indent, repeated for the deepest line so far
+    output_count: usize,         // bytes written so far, for max_output_bytes
+    comments: Vec<u8>,           // comments waiting for the next token
+    marks: Vec<CommentMark>,     // where each of them starts, and how to place it
+    line_start: bool,            // has a line of input ended since the last token?
+    spaced: bool,                // did the last token end with after_colon or after_comma?
+    pending: Pending,            // whitespace owed before the next token
+}
+
+/// ANSI escape sequences used by `Formatter.colors`, written before
+/// each kind of token.  Each token is followed by a reset sequence.
+///
+/// # Example:
+///
+/// ```
+/// let mut fmt = jsonxf::Formatter::minimizer();
+/// fmt.colors = Some(jsonxf::ColorScheme {
+///     number: String::from("\x1b[31m"),
+///     ..jsonxf::ColorScheme::default()
+/// });
+/// assert_eq!(fmt.format("7").unwrap(), "\x1b[31m7\x1b[0m");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorScheme {
+    /// Used for strings which are object keys.
+    pub key: String,
+    /// Used for all other strings.
+    pub string: String,
+    /// Used for numbers.
+    pub number: String,
+    /// Used for `true`, `false`, and `null`.
+    pub literal: String,
+    /// Used for brackets, commas and colons.
+    pub punctuation: String,
+}
+
+impl Default for ColorScheme {
+    /// Returns colors similar to those of `jq`.
+    fn default() -> ColorScheme {
+        ColorScheme {
+            key: String::from("\x1b[34;1m"),
+            string: String::from("\x1b[0;32m"),
+            number: String::from("\x1b[0;39m"),
+            literal: String::from("\x1b[0;33m"),
+            punctuation: String::from("\x1b[1;39m"),
+        }
+    }
+}
+
+const COLOR_RESET: &[u8] = b"\x1b[0m";
+
+/// How `Formatter.empty_style` writes empty arrays and objects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmptyStyle {
+    /// `{}` and `[]`
+    Compact,
+    /// `{ }` and `[ ]`
+    Spaced,
+    /// The closing bracket on its own line, as in `{\n}`
+    Expanded,
+}
+
+/// How blank lines in newline-delimited JSON are handled by
+/// [`Formatter::format_ndjson_stream`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlankLines {
+    /// Drop blank lines from the output.
+    Skip,
+    /// Output an empty record for each blank line.
+    Keep,
+}
+
+/// What [`Formatter`] does with comments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommentMode {
+    /// Comments aren't recognized, and are copied along with whatever
+    /// is around them as if they were numbers or literals.
+    Unrecognized,
+    /// Drop comments.
+    Strip,
+    /// Keep comments, moving them along with the structure around
+    /// them.  A comment which followed something on the same line
+    /// still does; one on a line of its own gets a line of its own at
+    /// the current indentation.  Comments are dropped by the options
+    /// which hold the whole input in memory, such as `sort_keys`.
+    Preserve,
+    /// Return `JsonxfError::Comment` for the first comment, as strict
+    /// JSON requires.
+    Reject,
+}
+
+/// What [`Formatter`] does with duplicate keys in an object.  Keys
+/// are compared after decoding their escapes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DuplicateKeys {
+    /// Output every member.
+    Keep,
+    /// Output only the first member with each key.
+    First,
+    /// Output only the last member with each key, as most JSON
+    /// parsers would read it.
+    Last,
+}
+
+/// How `Formatter.hex_case` writes the hex digits of `\uXXXX`
+/// escapes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HexCase {
+    /// Leave them as they are in the input.
+    Keep,
+    /// `\u00e9`
+    Lower,
+    /// `\u00E9`
+    Upper,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Comment {
+    None,
+    Slash,     // saw a `/`, which may begin a comment
+    Line,      // inside a `//` comment
+    Block,     // inside a `/*` comment
+    BlockStar, // saw a `*` inside a `/*` comment
+}
+
+// The offset of a colon in the output, and how many spaces to add
+// after it.
+type ColonPad = (usize, usize);
+
+// A comment kept by `CommentMode::Preserve`.
+#[derive(Clone)]
+struct CommentMark {
+    start: usize,        // offset in `comments`
+    own_line: bool,      // was it on a line of its own?
+    line: bool,          // is it a `//` comment?
+    newline_after: bool, // did a line of input end after it?
+}
+
+// Whitespace held back by `CommentMode::Preserve`, so that a comment can
+// still be written on the line before it.  Later variants win.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pending {
+    Nothing,
+    Space,   // after a block comment
+    Newline, // after a comma, or a block comment ending a line
+    LineEnd, // after a line comment, so even minimized output needs one
+}
+
+impl Default for Formatter {
+    /// Returns a pretty-printer, the same as `Formatter::pretty_printer()`.
+    fn default() -> Formatter {
+        Formatter {
+            indent: String::from("  "),
+            depth_indents: vec![],
+            detect_indent: false,
+            array_indent: None,
+            object_indent: None,
+            base_indent_level: 0,
+            max_indent_level: None,
+            expand_to_depth: None,
+            line_separator: String::from("\n"),
+            record_separator: String::from("\n"),
+            before_colon: String::from(""),
+            after_colon: String::from(" "),
+            bracket_padding: String::from(""),
+            after_array_open: None,
+            before_array_close: None,
+            after_object_open: None,
+            before_object_close: None,
+            after_comma: String::from(""),
+            trailing_output: String::from(""),
+            eager_record_separators: false,
+            flush_after_documents: false,
+            comment_mode: CommentMode::Unrecognized,
+            remove_trailing_commas: false,
+            add_trailing_commas: false,
+            empty_style: EmptyStyle::Compact,
+            strict: false,
+            max_depth: None,
+            single_document: false,
+            reject_leading_junk: false,
+            max_output_bytes: None,
+            sort_keys: false,
+            ascii_only: false,
+            max_inline_length: None,
+            wrap_width: None,
+            align_values: false,
+            normalize_numbers: false,
+            validate_numbers: false,
+            nonfinite_to_null: false,
+            unescape_unicode: false,
+            escape_slashes: false,
+            escape_html: false,
+            remove_needless_escapes: false,
+            hex_case: HexCase::Keep,
+            canonicalize_string_whitespace: false,
+            colors: None,
+            preserve_bom: false,
+            decode_utf16: false,
+            single_quotes: false,
+            quote_keys: false,
+            buffer_capacity: 8 * 1024,
+            duplicate_keys: DuplicateKeys::Keep,
+            redact_keys: vec![],
+            redact_non_strings: false,
+            blank_lines: BlankLines::Skip,
+            depth: 0,
+            in_string: false,
+            in_backslash: false,
+            empty: false,
+            first: true,
+            in_scalar: false,
+            after_scalar: false,
+            comment: Comment::None,
+            pending_comma: false,
+            offset: 0,
+            buffer: vec![],
+            utf8_buf: [0; 4],
+            utf8_len: 0,
+            utf8_need: 0,
+            utf8_start: 0,
+            escape_buf: [0; 12],
+            escape_len: 0,
+            bom_checked: false,
+            indent_checked: false,
+            indent_sample: vec![],
+            seen_indent: None,
+            utf16: Utf16Decoder::new(),
+            inline_starts: vec![],
+            inline_next: 0,
+            inline_depth: None,
+            inline_minimized: false,
+            colon_pads: vec![],
+            colon_next: 0,
+            structure: vec![],
+            expect_key: false,
+            stats: Stats::default(),
+            scalar_buf: vec![],
+            string_start: 0,
+            scalar_start: 0,
+            string_chars: 0,
+            in_key: false,
+            quote: C_QUOTE,
+            indent_buf: vec![],
+            output_count: 0,
+            comments: vec![],
+            marks: vec![],
+            line_start: true,
+            spaced: false,
+            pending: Pending::Nothing,
+        }
+    }
+}
+
+// Shows the settings, leaving out the state of any formatting under way.
+impl fmt::Debug for Formatter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Formatter")
+            .field("indent", &self.indent)
+            .field("depth_indents", &self.depth_indents)
+            .field("detect_indent", &self.detect_indent)
+            .field("array_indent", &self.array_indent)
+            .field("object_indent", &self.object_indent)
+            .field("base_indent_level", &self.base_indent_level)
+            .field("max_indent_level", &self.max_indent_level)
+            .field("expand_to_depth", &self.expand_to_depth)
+            .field("line_separator", &self.line_separator)
+            .field("record_separator", &self.record_separator)
+            .field("before_colon", &self.before_colon)
+            .field("after_colon", &self.after_colon)
+            .field("bracket_padding", &self.bracket_padding)
+            .field("after_array_open", &self.after_array_open)
+            .field("before_array_close", &self.before_array_close)
+            .field("after_object_open", &self.after_object_open)
+            .field("before_object_close", &self.before_object_close)
+            .field("after_comma", &self.after_comma)
+            .field("trailing_output", &self.trailing_output)
+            .field("eager_record_separators", &self.eager_record_separators)
+            .field("flush_after_documents", &self.flush_after_documents)
+            .field("comment_mode", &self.comment_mode)
+            .field("remove_trailing_commas", &self.remove_trailing_commas)
+            .field("add_trailing_commas", &self.add_trailing_commas)
+            .field("empty_style", &self.empty_style)
+            .field("strict", &self.strict)
+            .field("max_depth", &self.max_depth)
+            .field("single_document", &self.single_document)
+            .field("reject_leading_junk", &self.reject_leading_junk)
+            .field("max_output_bytes", &self.max_output_bytes)
+            .field("sort_keys", &self.sort_keys)
+            .field("ascii_only", &self.ascii_only)
+            .field("max_inline_length", &self.max_inline_length)
+            .field("wrap_width", &self.wrap_width)
+            .field("align_values", &self.align_values)
+            .field("normalize_numbers", &self.normalize_numbers)
+            .field("validate_numbers", &self.validate_numbers)
+            .field("nonfinite_to_null", &self.nonfinite_to_null)
+            .field("unescape_unicode", &self.unescape_unicode)
+            .field("escape_slashes", &self.escape_slashes)
+            .field("escape_html", &self.escape_html)
+            .field("remove_needless_escapes", &self.remove_needless_escapes)
+            .field("hex_case", &self.hex_case)
+            .field(
+                "canonicalize_string_whitespace",
+                &self.canonicalize_string_whitespace,
+            )
+            .field("duplicate_keys", &self.duplicate_keys)
+            .field("redact_keys", &self.redact_keys)
+            .field("redact_non_strings", &self.redact_non_strings)
+            .field("blank_lines", &self.blank_lines)
+            .field("colors", &self.colors)
+            .field("preserve_bom", &self.preserve_bom)
+            .field("decode_utf16", &self.decode_utf16)
+            .field("single_quotes", &self.single_quotes)
+            .field("quote_keys", &self.quote_keys)
+            .field("buffer_capacity", &self.buffer_capacity)
+            .finish()
+    }
+}
+
+impl Formatter {
+    /// Clears all state left from previous input, so the formatter can
+    /// be used on another document.  Settings are kept, as is memory
+    /// allocated for `sort_keys` and similar options.
+    ///
+    /// The `format` methods do this themselves; call it between
+    /// documents fed in through [`Formatter::format_buf`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// let mut first: Vec<u8> = vec![];
+    /// fmt.format_buf(b"[1, 2]", &mut first).unwrap();
+    /// fmt.finish(&mut first).unwrap();
+    ///
+    /// fmt.reset();
+    /// let mut second: Vec<u8> = vec![];
+    /// fmt.format_buf(b"[3]", &mut second).unwrap();
+    /// fmt.finish(&mut second).unwrap();
+    /// assert_eq!(second, b"[3]");
+    /// ```
+    pub fn reset(&mut self) {
+        self.depth = 0;
+        self.in_string = false;
+        self.in_backslash = false;
+        self.empty = false;
+        self.first = true;
+        self.in_scalar = false;
+        self.after_scalar = false;
+        self.comment = Comment::None;
+        self.pending_comma = false;
+        self.offset = 0;
+        self.buffer.clear();
+        self.utf8_len = 0;
+        self.utf8_need = 0;
+        self.escape_len = 0;
+        self.bom_checked = false;
+        self.indent_checked = false;
+        self.indent_sample.clear();
+        self.seen_indent = None;
+        self.utf16.reset();
+        self.inline_starts.clear();
+        self.inline_next = 0;
+        self.inline_depth = None;
+        self.inline_minimized = false;
+        self.colon_pads.clear();
+        self.colon_next = 0;
+        self.structure.clear();
+        self.expect_key = false;
+        self.stats = Stats::default();
+        self.scalar_buf.clear();
+        self.indent_buf.clear();
+        self.output_count = 0;
+        self.comments.clear();
+        self.marks.clear();
+        self.line_start = true;
+        self.spaced = false;
+        self.pending = Pending::Nothing;
+    }
+
+    // Do the options in use require holding the whole input in memory?
+    fn is_buffered(&self) -> bool {
+        self.sort_keys
+            || self.max_inline_length.is_some()
+            || self.wrap_width.is_some()
+            || self.align_values
+            || self.duplicate_keys != DuplicateKeys::Keep
+            || !self.redact_keys.is_empty()
+    }
+
+    /// Returns a Formatter set up for pretty-printing.
+    /// Defaults to using two spaces of indentation,
+    /// Unix newlines, and no whitespace at EOF.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     jsonxf::Formatter::pretty_printer().format("{\"a\":1}").unwrap(),
+    ///     "{\n  \"a\": 1\n}"
+    /// );
+    /// ```
+    pub fn pretty_printer() -> Formatter {
+        Formatter::default()
+    }
+
+    /// Returns a Formatter set up for minimizing.
+    /// Defaults to using Unix newlines between records,
+    /// and no whitespace at EOF.  For output ending in a newline,
+    /// as many tools expect of files, set `trailing_output` to `"\n"`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     jsonxf::Formatter::minimizer().format("{  \"a\" : 1  }\n").unwrap(),
+    ///     "{\"a\":1}"
+    /// );
+    ///
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// fmt.trailing_output = String::from("\n");
+    /// assert_eq!(fmt.format("[1]\n\n[2]\n").unwrap(), "[1]\n[2]\n");
+    /// ```
+    pub fn minimizer() -> Formatter {
+        Formatter {
+            indent: String::from(""),
+            line_separator: String::from(""),
+            record_separator: String::from("\n"),
+            after_colon: String::from(""),
+            ..Formatter::default()
+        }
+    }
+
+    /// Returns a Formatter which minimizes, except for a single space
+    /// after each comma and colon.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     jsonxf::Formatter::spaced_minimizer().format("{ \"a\": \"b\", \"c\": [0, 1] }").unwrap(),
+    ///     "{\"a\": \"b\", \"c\": [0, 1]}"
+    /// );
+    /// ```
+    pub fn spaced_minimizer() -> Formatter {
+        let mut xf = Formatter::minimizer();
+        xf.after_comma = String::from(" ");
+        xf.after_colon = String::from(" ");
+        xf
+    }
+
+    /// Returns a Formatter set up for readable output on a single
+    /// line, with spaces inside brackets and after commas and colons.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     jsonxf::Formatter::single_line().format("{\"a\":[1,2],\"b\":{}}").unwrap(),
+    ///     "{ \"a\": [ 1, 2 ], \"b\": {} }"
+    /// );
+    /// ```
+    pub fn single_line() -> Formatter {
+        let mut xf = Formatter::spaced_minimizer();
+        xf.bracket_padding = String::from(" ");
+        xf
+    }
+
+    /// Returns a Formatter which writes what Python's
+    /// `json.dumps(obj, indent=indent)` does, given the same data:
+    /// `indent` spaces per level, `": "` after keys, nothing after
+    /// commas, and non-ASCII characters escaped.  Needless escapes in
+    /// the input are removed, but Python also rewrites the escapes it
+    /// keeps, such as `\u000A` as `\n`, and numbers, which this does
+    /// not.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     jsonxf::Formatter::python_compatible(2).format("{\"a\":[\"\u{e9}\",{}]}").unwrap(),
+    ///     "{\n  \"a\": [\n    \"\\u00e9\",\n    {}\n  ]\n}"
+    /// );
+    /// ```
+    pub fn python_compatible(indent: usize) -> Formatter {
+        let mut xf = Formatter::pretty_printer().with_spaces(indent);
+        xf.ascii_only = true;
+        xf.remove_needless_escapes = true;
+        xf
+    }
+
+    /// Returns this Formatter using one tab character per level of
+    /// indentation.  Whatever indentation the input has is discarded.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer().with_tabs();
+    /// assert_eq!(fmt.format("[\n    [1]]").unwrap(), "[\n\t[\n\t\t1\n\t]\n]");
+    /// ```
+    pub fn with_tabs(self) -> Formatter {
+        self.indent_tabs(1)
+    }
+
+    /// Returns this Formatter using `n` spaces per level of
+    /// indentation.  Whatever indentation the input has is discarded.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer().with_spaces(4);
+    /// assert_eq!(fmt.format("[\n\t[1]]").unwrap(), "[\n    [\n        1\n    ]\n]");
+    /// ```
+    pub fn with_spaces(self, n: usize) -> Formatter {
+        self.indent_spaces(n)
+    }
+
+    /// Returns this Formatter using `n` tab characters per level of
+    /// indentation, as [`Formatter::with_tabs`] does for one.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer().indent_tabs(2);
+    /// assert_eq!(fmt.format("[[1]]").unwrap(), "[\n\t\t[\n\t\t\t\t1\n\t\t]\n]");
+    /// ```
+    pub fn indent_tabs(mut self, n: usize) -> Formatter {
+        self.indent = "\t".repeat(n);
+        self
+    }
+
+    /// Returns this Formatter using `n` spaces per level of
+    /// indentation.  The same as [`Formatter::with_spaces`], named to
+    /// go with [`Formatter::indent_tabs`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer().indent_spaces(1);
+    /// assert_eq!(fmt.format("[[1]]").unwrap(), "[\n [\n  1\n ]\n]");
+    /// ```
+    pub fn indent_spaces(mut self, n: usize) -> Formatter {
+        self.indent = " ".repeat(n);
+        self
+    }
+
+    /// Returns this Formatter ending lines with `ending`, such as
+    /// `"\r\n"` for Windows.  Wherever `line_separator`,
+    /// `record_separator` or `trailing_output` is a newline, it is
+    /// changed to `ending`; a minimizer still writes no line breaks
+    /// inside documents.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer().with_line_ending("\r\n");
+    /// assert_eq!(fmt.format("[1] [2]").unwrap(), "[\r\n  1\r\n]\r\n[\r\n  2\r\n]");
+    /// ```
+    pub fn with_line_ending(mut self, ending: &str) -> Formatter {
+        for s in [
+            &mut self.line_separator,
+            &mut self.record_separator,
+            &mut self.trailing_output,
+        ] {
+            if s == "\n" {
+                *s = String::from(ending);
+            }
+        }
+        self
+    }
+
+    /// Formats a string of JSON-encoded data.
+    ///
+    /// Input must be valid JSON data in UTF-8 encoding.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.indent = String::from("\t");
+    /// fmt.trailing_output = String::from("\n");
+    /// assert_eq!(
+    ///     fmt.format("{\"a\":1}").unwrap(),
+    ///     "{\n\t\"a\": 1\n}\n"
+    /// );
+    /// ```
+    pub fn format(&mut self, json_string: &str) -> Result<String, JsonxfError> {
+        let output = self.format_bytes(json_string.as_bytes())?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    /// Formats a byte slice of JSON-encoded data.
+    ///
+    /// Unlike [`Formatter::format`], the output is not checked to be
+    /// valid UTF-8, so this can be used on JSON-like data whose strings
+    /// contain arbitrary bytes.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// assert_eq!(
+    ///     fmt.format_bytes(b"[ \"\xff\" ]").unwrap(),
+    ///     b"[\"\xff\"]"
+    /// );
+    /// ```
+    pub fn format_bytes(&mut self, json_bytes: &[u8]) -> Result<Vec<u8>, JsonxfError> {
+        let mut output: Vec<u8> = Vec::with_capacity(json_bytes.len());
+        self.reset();
+        self.format_buf(json_bytes, &mut output)?;
+        self.finish(&mut output)?;
+        Ok(output)
+    }
+
+    /// Formats only the value which a JSON Pointer (RFC 6901), such as
+    /// `/results/0/data`, points to in the first document of the input.
+    /// An empty pointer means the whole document.
+    ///
+    /// Like `sort_keys`, this holds the whole input in memory.  Returns
+    /// `JsonxfError::PointerNotFound` if nothing matches the pointer.
+    /// The input may be a string or bytes, such as UTF-16 ones for
+    /// `decode_utf16`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// assert_eq!(
+    ///     fmt.format_pointer("{\"results\":[{\"data\":[1]}]}", "/results/0").unwrap(),
+    ///     "{\n  \"data\": [\n    1\n  ]\n}"
+    /// );
+    /// ```
+    pub fn format_pointer<J: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        json: &J,
+        pointer: &str,
+    ) -> Result<String, JsonxfError> {
+        self.reset();
+        let minimized = self.minimize_for_tree(json.as_ref())?;
+        let mut tree = Tree::parse(&minimized);
+        self.rearrange(&mut tree);
+        let node = tree
+            .roots
+            .first()
+            .and_then(|&root| tree.pointer(root, pointer))
+            .ok_or_else(|| JsonxfError::PointerNotFound {
+                pointer: String::from(pointer),
+            })?;
+        let mut output: Vec<u8> = vec![];
+        self.write_tree(&tree, &[node], &mut output)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    /// Lists the keys which appear more than once in the same object,
+    /// as pairs of the JSON Pointer of the object and the decoded key,
+    /// without formatting anything.  Each key is listed once per object
+    /// it is repeated in, and the pointers of values in a stream of
+    /// documents start from the document they are in.
+    ///
+    /// Like `sort_keys`, this holds the whole input in memory.  As with
+    /// [`Formatter::format_pointer`], the input may be a string or bytes.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// assert_eq!(
+    ///     fmt.find_duplicate_keys("{\"a\": [{\"b\": 1, \"b\": 2}], \"a\": 3}").unwrap(),
+    ///     vec![
+    ///         (String::from(""), String::from("a")),
+    ///         (String::from("/a/0"), String::from("b")),
+    ///     ]
+    /// );
+    /// ```
+    pub fn find_duplicate_keys<J: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        json: &J,
+    ) -> Result<Vec<(String, String)>, JsonxfError> {
+        self.reset();
+        let minimized = self.minimize_for_tree(json.as_ref())?;
+        let tree = Tree::parse(&minimized);
+        let mut found = vec![];
+        for &root in tree.roots.iter() {
+            found.extend(tree.duplicate_keys(root));
+        }
+        Ok(found)
+    }
+
+    /// Formats a stream of JSON-encoded data.
+    ///
+    /// Input must be valid JSON data in UTF-8 encoding.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.indent = String::from("\t");
+    /// fmt.trailing_output = String::from("\n");
+    /// match fmt.format_stream(&mut std::io::stdin(), &mut std::io::stdout()) {
+    ///     Ok(_) => { /* YAY */ },
+    ///     Err(e) => { panic!(e.to_string()); }
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format_stream<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), JsonxfError> {
+        let mut reader = BufReader::with_capacity(self.buffer_capacity, input);
+        let mut writer = BufWriter::with_capacity(self.buffer_capacity, output);
+        self.format_stream_unbuffered(&mut reader, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Formats a stream of JSON-encoded data like
+    /// [`Formatter::format_stream`], returning figures such as the
+    /// number of bytes read and written.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// let mut input: &[u8] = b"{ \"a\": [ 1, \"b\" ] }";
+    /// let mut output: Vec<u8> = vec![];
+    /// let stats = fmt.format_stream_with_stats(&mut input, &mut output).unwrap();
+    /// assert_eq!(stats.input_bytes, 19);
+    /// assert_eq!(stats.output_bytes, 13);
+    /// assert_eq!(stats.max_depth, 2);
+    /// assert_eq!((stats.objects, stats.arrays, stats.strings), (1, 1, 1));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format_stream_with_stats<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<Stats, JsonxfError> {
+        let mut reader = CountingReader {
+            inner: input,
+            count: 0,
+        };
+        let mut writer = CountingWriter {
+            inner: output,
+            count: 0,
+        };
+        self.format_stream(&mut reader, &mut writer)?;
+        let mut stats = self.stats;
+        stats.input_bytes = reader.count;
+        stats.output_bytes = writer.count;
+        Ok(stats)
+    }
+
+    /// Formats a stream of JSON-encoded data like
+    /// [`Formatter::format_stream`], calling `progress` with the number
+    /// of bytes read so far each time at least `interval` more have
+    /// been read, and once more when the input ends.  Input is read a
+    /// buffer at a time, so the calls come no more often than that.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::minimizer();
+    /// let mut input: &[u8] = b"[1, 2, 3]";
+    /// let mut output: Vec<u8> = vec![];
+    /// let mut seen = vec![];
+    /// fmt.format_stream_with_progress(&mut input, &mut output, 1024, |n| seen.push(n))
+    ///     .unwrap();
+    /// assert_eq!(output, b"[1,2,3]");
+    /// assert_eq!(seen, vec![9]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format_stream_with_progress<R: Read, W: Write, F: FnMut(u64)>(
+        &mut self,
+        input: R,
+        output: W,
+        interval: u64,
+        progress: F,
+    ) -> Result<(), JsonxfError> {
+        let reader = ProgressReader {
+            inner: input,
+            count: 0,
+            next: interval,
+            interval,
+            progress,
+            reported: None,
+        };
+        self.format_stream(reader, output)
+    }
+
+    /// Formats a stream of JSON-encoded data without buffering.
+    ///
+    /// This will perform many small writes, so it's advisable to use an
+    /// output that does its own buffering. In simple cases, use
+    /// [`Formatter::format_stream`] instead.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// let mut stdin = std::io::stdin();
+    /// let mut stdout = std::io::stdout();
+    /// fmt.format_stream_unbuffered(&mut stdin, &mut std::io::LineWriter::new(stdout))
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format_stream_unbuffered(
+        &mut self,
+        input: &mut impl Read,
+        output: &mut impl Write,
+    ) -> Result<(), JsonxfError> {
+        self.reset();
+        let mut buf = vec![0_u8; self.buffer_capacity.max(1)];
+        loop {
+            match input.read(&mut buf) {
+                Ok(0) => {
+                    break;
+                }
+                Ok(n) => {
+                    self.format_buf(&buf[0..n], output)?;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {
+                    continue;
+                }
+                Err(e) => {
+                    return Err(JsonxfError::Io(e));
+                }
+            }
+        }
+        self.finish(output)
+    }
+
+    /// Formats a stream of newline-delimited JSON (JSON Lines), where
+    /// each line of input is a separate document.
+    ///
+    /// Each line is formatted on its own, so a broken line does not
+    /// affect the ones after it.  The results are joined with the
+    /// `record_separator`, and blank lines are handled according to
+    /// `blank_lines`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.blank_lines = jsonxf::BlankLines::Keep;
+    /// let mut input: &[u8] = b"{\"a\":[1]}\n\n\"b\"\n";
+    /// let mut output: Vec<u8> = vec![];
+    /// fmt.format_ndjson_stream(&mut input, &mut output).unwrap();
+    /// assert_eq!(output, b"{\n  \"a\": [\n    1\n  ]\n}\n\n\"b\"");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn format_ndjson_stream<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), JsonxfError> {
+        let mut reader = BufReader::with_capacity(self.buffer_capacity, input);
+        let mut buffered = BufWriter::with_capacity(self.buffer_capacity, output);
+        // Lines are formatted separately, so the limit is applied here
+        let limit = self.max_output_bytes.unwrap_or(usize::MAX);
+        let mut writer = LimitedWriter {
+            inner: &mut buffered,
+            remaining: limit,
+            exceeded: false,
+        };
+        let result = self.format_ndjson_lines(&mut reader, &mut writer);
+        if writer.exceeded {
+            return Err(JsonxfError::OutputTooLarge { limit });
+        }
+        result?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Formats each line of `reader`, for `format_ndjson_stream`.
+    #[cfg(feature = "std")]
+    fn format_ndjson_lines(
+        &self,
+        reader: &mut impl BufRead,
+        writer: &mut impl Write,
+    ) -> Result<(), JsonxfError> {
+        let mut line: Vec<u8> = vec![];
+        let mut xf = self.clone();
+        xf.eager_record_separators = false;
+        xf.trailing_output = String::from("");
+        xf.max_output_bytes = None;
+        let mut first = true;
+        loop {
+            line.clear();
+            if reader.read_until(C_LF, &mut line)? == 0 {
+                break;
+            }
+            let blank = line.iter().all(|&b| is_whitespace(b));
+            if blank && self.blank_lines == BlankLines::Skip {
+                continue;
+            }
+
+            if !first && !self.eager_record_separators {
+                writer.write_all(self.record_separator.as_bytes())?;
+            }
+            first = false;
+            if !blank {
+                xf.reset();
+                xf.format_buf(&line, writer)?;
+                xf.finish(writer)?;
+            }
+            if self.eager_record_separators {
+                writer.write_all(self.record_separator.as_bytes())?;
+            }
+        }
+        writer.write_all(self.trailing_output.as_bytes())?;
+        Ok(())
+    }
+
+    /// Format directly from a buffer into a writer.
+    ///
+    /// This may be called on chunks of a JSON document to format it bit by bit.
+    ///
+    /// As such, it does not add the `trailing_output` at the end;
+    /// call [`Formatter::finish`] once the input is exhausted.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// let text = "[1, 2, 3]";
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// let mut stdout = std::io::stdout();
+    /// fmt.format_buf(text.as_bytes(), &mut stdout).unwrap();
+    /// ```
+    pub fn format_buf(&mut self, buf: &[u8], writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.decode_utf16 {
+            let mut decoded = Vec::with_capacity(buf.len());
+            self.utf16.decode(buf, &mut decoded);
+            return self.format_sampled(&decoded, writer);
+        }
+        self.format_sampled(buf, writer)
+    }
+
+    // Holds input back for `detect_indent` until it shows whether the
+    // input is indented.
+    fn format_sampled(&mut self, buf: &[u8], writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if !self.detect_indent || self.indent_checked || self.line_separator.is_empty() {
+            return self.format_limited(buf, writer);
+        }
+        self.indent_sample.extend_from_slice(buf);
+        match sample_indent(&self.indent_sample) {
+            IndentSample::NeedMore if self.indent_sample.len() < MAX_INDENT_SAMPLE => {
+                return Ok(());
+            }
+            IndentSample::Found(indent) => self.seen_indent = Some(indent),
+            _ => {}
+        }
+        self.end_indent_sample(writer)
+    }
+
+    // Formats the input held back by `format_sampled`.
+    fn end_indent_sample(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        self.indent_checked = true;
+        let sample = std::mem::take(&mut self.indent_sample);
+        self.format_limited(&sample, writer)
+    }
+
+    // The indentation for one level, from `detect_indent` or `indent`.
+    fn indent(&self) -> &str {
+        self.seen_indent.as_ref().unwrap_or(&self.indent)
+    }
+
+    fn format_limited(&mut self, buf: &[u8], writer: &mut impl Write) -> Result<(), JsonxfError> {
+        match self.max_output_bytes {
+            None => self.format_chunk(buf, writer),
+            Some(limit) => {
+                let mut limited = LimitedWriter {
+                    inner: writer,
+                    remaining: limit.saturating_sub(self.output_count),
+                    exceeded: false,
+                };
+                let result = self.format_chunk(buf, &mut limited);
+                self.count_output(limit, limited)?;
+                result
+            }
+        }
+    }
+
+    #[allow(clippy::unnecessary_map_or)] // is_some_and needs Rust 1.70
+    fn format_chunk(&mut self, buf: &[u8], writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.is_buffered() {
+            self.buffer.extend_from_slice(buf);
+            return Ok(());
+        }
+
+        let mut n = 0;
+        if !self.bom_checked {
+            while n < buf.len() && self.offset + n < 3 && buf[n] == UTF8_BOM[self.offset + n] {
+                n += 1;
+            }
+            if self.offset + n == 3 {
+                self.bom_checked = true;
+                if self.preserve_bom {
+                    writer.write_all(&UTF8_BOM)?;
+                }
+            } else if n == buf.len() {
+                // Could still be a byte order mark; wait for more input
+                self.offset += buf.len();
+                return Ok(());
+            } else {
+                self.bom_checked = true;
+                self.replay_bom_prefix(writer)?;
+                n = 0;
+            }
+        }
+
+        while n < buf.len() {
+            let b = buf[n];
+
+            if self.in_string {
+                if self.slow_strings() {
+                    let start = self.offset + n;
+                    n += self.format_string(&buf[n..], start, writer)?;
+                    continue;
+                }
+                if self.in_backslash {
+                    writer.write_all(&buf[n..n + 1])?;
+                    self.string_chars += char_count(&buf[n..n + 1]);
+                    self.in_backslash = false;
+                } else {
+                    match memchr::memchr2(C_QUOTE, C_BACKSLASH, &buf[n..]) {
+                        None => {
+                            // The whole rest of buf is part of the string
+                            writer.write_all(&buf[n..])?;
+                            self.string_chars += char_count(&buf[n..]);
+                            break;
+                        }
+                        Some(index) => {
+                            let length = index + 1;
+                            writer.write_all(&buf[n..n + length])?;
+                            self.string_chars += char_count(&buf[n..n + index]);
+                            if buf[n + index] == C_QUOTE {
+                                self.end_string(writer)?;
+                            } else {
+                                // Backslash
+                                self.string_chars += 1;
+                                self.in_backslash = true;
+                            }
+                            n += length;
+                            continue;
+                        }
+                    }
+                }
+            } else {
+                if self.comment_mode != CommentMode::Unrecognized
+                    && self.format_comment_byte(b, self.offset + n, writer)?
+                {
+                    n += 1;
+                    continue;
+                }
+
+                if self.in_scalar && is_delimiter(b) {
+                    self.end_scalar(writer)?;
+                }
+
+                if self.single_document
+                    && self.depth == 0
+                    && !self.first
+                    && !self.in_scalar
+                    && !is_whitespace(b)
+                {
+                    return Err(JsonxfError::TrailingData {
+                        offset: self.offset + n,
+                    });
+                }
+
+                if self.reject_leading_junk && self.first && !is_whitespace(b) {
+                    let start = match b {
+                        C_LEFT_BRACE | C_LEFT_BRACKET | C_QUOTE | b'-' | b't' | b'f' | b'n' => true,
+                        C_APOSTROPHE => self.single_quotes,
+                        _ => b.is_ascii_digit(),
+                    };
+                    if !start {
+                        return Err(JsonxfError::LeadingJunk {
+                            byte: b,
+                            offset: self.offset + n,
+                        });
+                    }
+                }
+
+                if self.pending_comma && !is_whitespace(b) && b != C_COMMA {
+                    self.pending_comma = false;
+                    if b != C_RIGHT_BRACE && b != C_RIGHT_BRACKET {
+                        self.write_comma(writer)?;
+                    }
+                }
+
+                if (b == C_RIGHT_BRACKET || b == C_RIGHT_BRACE)
+                    && self.add_trailing_commas
+                    && !self.empty
+                    && self.depth > 0
+                    && self.inline_depth.is_none()
+                    && !self.line_separator.is_empty()
+                {
+                    // Before any comments after the last element
+                    self.write_punctuation(C_COMMA, writer)?;
+                }
+
+                if self.keep_comments() {
+                    if !is_whitespace(b) {
+                        self.before_token(b, writer)?;
+                    } else if b == C_LF {
+                        self.line_start = true;
+                        if let Some(mark) = self.marks.last_mut() {
+                            mark.newline_after = true;
+                        }
+                    }
+                }
+
+                match b {
+                    C_SPACE | C_LF | C_CR | C_TAB => {
+                        // skip whitespace
+                    }
+
+                    C_LEFT_BRACKET | C_LEFT_BRACE => {
+                        if let Some(max_depth) = self.max_depth {
+                            if self.depth >= max_depth {
+                                return Err(JsonxfError::MaxDepthExceeded {
+                                    depth: self.depth + 1,
+                                    offset: self.offset + n,
+                                });
+                            }
+                        }
+                        if self.first {
+                            self.first = false;
+                        } else if self.empty {
+                            self.start_first_item(writer)?;
+                        } else if !self.eager_record_separators && self.depth == 0 {
+                            writer.write_all(self.record_separator.as_bytes())?;
+                        }
+                        if self.depth == 0 {
+                            self.stats.documents += 1;
+                            if self.base_indent_level > 0 || !self.depth_indents.is_empty() {
+                                self.write_indentation(writer)?;
+                            }
+                        }
+                        self.write_punctuation(b, writer)?;
+                        if self.inline_starts.get(self.inline_next) == Some(&(self.offset + n)) {
+                            self.inline_next += 1;
+                            if self.inline_depth.is_none() {
+                                self.inline_depth = Some(self.depth);
+                            }
+                        }
+                        if self.inline_depth.is_none()
+                            && self.expand_to_depth.map_or(false, |d| self.depth >= d)
+                        {
+                            self.inline_depth = Some(self.depth);
+                            self.inline_minimized = true;
+                        }
+                        self.depth += 1;
+                        self.empty = true;
+                        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+                        if b == C_LEFT_BRACE {
+                            self.stats.objects += 1;
+                        } else {
+                            self.stats.arrays += 1;
+                        }
+                        self.structure.push(b);
+                        self.expect_key = b == C_LEFT_BRACE;
+                    }
+
+                    C_RIGHT_BRACKET | C_RIGHT_BRACE => {
+                        if self.strict && self.depth == 0 {
+                            return Err(JsonxfError::UnexpectedClose {
+                                offset: self.offset + n,
+                            });
+                        }
+                        let open = self.structure.pop();
+                        if self.strict {
+                            let expected = match open {
+                                Some(C_LEFT_BRACE) => C_RIGHT_BRACE,
+                                _ => C_RIGHT_BRACKET,
+                            };
+                            if b != expected {
+                                return Err(JsonxfError::MismatchedClose {
+                                    expected: expected as char,
+                                    found: b as char,
+                                    offset: self.offset + n,
+                                });
+                            }
+                        }
+                        self.depth = self.depth.saturating_sub(1);
+                        self.expect_key = false;
+                        if self.inline_minimized {
+                            self.empty = false;
+                        } else if !self.empty {
+                            self.write_newline(writer)?;
+                            if self.on_one_line() {
+                                let padding = match open {
+                                    Some(C_LEFT_BRACE) => self.before_object_close.as_ref(),
+                                    _ => self.before_array_close.as_ref(),
+                                };
+                                writer.write_all(
+                                    padding.unwrap_or(&self.bracket_padding).as_bytes(),
+                                )?;
+                            }
+                        } else {
+                            self.empty = false;
+                            match self.empty_style {
+                                EmptyStyle::Compact => {}
+                                EmptyStyle::Spaced => writer.write_all(&[C_SPACE])?,
+                                EmptyStyle::Expanded => self.write_newline(writer)?,
+                            }
+                        }
+                        self.write_punctuation(b, writer)?;
+                        if self.inline_depth == Some(self.depth) {
+                            self.inline_depth = None;
+                            self.inline_minimized = false;
+                        }
+                        if self.depth == 0 {
+                            self.end_document(writer)?;
+                        }
+                    }
+
+                    C_COMMA => {
+                        self.expect_key = self.structure.last() == Some(&C_LEFT_BRACE);
+                        if self.remove_trailing_commas || self.add_trailing_commas {
+                            self.pending_comma = true;
+                        } else {
+                            self.write_comma(writer)?;
+                        }
+                    }
+
+                    C_COLON => {
+                        self.expect_key = false;
+                        if let Some(&(offset, pad)) = self.colon_pads.get(self.colon_next) {
+                            if offset == self.offset + n {
+                                self.colon_next += 1;
+                                for _ in 0..pad {
+                                    writer.write_all(&[C_SPACE])?;
+                                }
+                            }
+                        }
+                        if self.inline_minimized {
+                            self.write_punctuation(b, writer)?;
+                        } else {
+                            writer.write_all(self.before_colon.as_bytes())?;
+                            self.write_punctuation(b, writer)?;
+                            writer.write_all(self.after_colon.as_bytes())?;
+                        }
+                    }
+
+                    _ => {
+                        if b == C_QUOTE || b == C_APOSTROPHE {
+                            self.string_start = self.offset + n;
+                        } else if !self.in_scalar {
+                            self.scalar_start = self.offset + n;
+                        }
+                        self.format_value_byte(b, writer)?;
+                        if self.in_scalar {
+                            // Copy the rest of a number or literal in one write
+                            let rest = &buf[n + 1..];
+                            let run = rest
+                                .iter()
+                                .position(|&c| {
+                                    is_delimiter(c)
+                                        || (self.comment_mode != CommentMode::Unrecognized
+                                            && c == C_SLASH)
+                                })
+                                .unwrap_or(rest.len());
+                            if self.holds_scalars() {
+                                self.scalar_buf.extend_from_slice(&rest[..run]);
+                            } else {
+                                writer.write_all(&rest[..run])?;
+                            }
+                            n += run;
+                        }
+                    }
+                };
+            };
+            n += 1;
+        }
+
+        self.offset += buf.len();
+        Ok(())
+    }
+
+    /// Finishes formatting after the last call to
+    /// [`Formatter::format_buf`], writing anything still pending
+    /// followed by the `trailing_output`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let mut fmt = jsonxf::Formatter::pretty_printer();
+    /// fmt.eager_record_separators = true;
+    /// let mut output: Vec<u8> = vec![];
+    /// fmt.format_buf(b"4", &mut output).unwrap();
+    /// fmt.format_buf(b"2", &mut output).unwrap();
+    /// fmt.finish(&mut output).unwrap();
+    /// assert_eq!(output, b"42\n");
+    /// ```
+    pub fn finish(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.decode_utf16 {
+            let mut rest = vec![];
+            self.utf16.finish(&mut rest);
+            self.format_sampled(&rest, writer)?;
+        }
+        if !self.indent_sample.is_empty() {
+            self.end_indent_sample(writer)?;
+        }
+        match self.max_output_bytes {
+            None => self.finish_chunks(writer),
+            Some(limit) => {
+                let mut limited = LimitedWriter {
+                    inner: writer,
+                    remaining: limit.saturating_sub(self.output_count),
+                    exceeded: false,
+                };
+                let result = self.finish_chunks(&mut limited);
+                self.count_output(limit, limited)?;
+                result
+            }
+        }
+    }
+
+    // Adds what went through `limited` to the output so far.
+    fn count_output<W: Write + ?Sized>(
+        &mut self,
+        limit: usize,
+        limited: LimitedWriter<W>,
+    ) -> Result<(), JsonxfError> {
+        self.output_count = limit - limited.remaining;
+        if limited.exceeded {
+            return Err(JsonxfError::OutputTooLarge { limit });
+        }
+        Ok(())
+    }
+
+    fn finish_chunks(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.is_buffered() {
+            let input = std::mem::take(&mut self.buffer);
+            return self.format_tree(&input, writer);
+        }
+        if !self.bom_checked {
+            self.bom_checked = true;
+            self.replay_bom_prefix(writer)?;
+        }
+        if self.strict && self.in_string {
+            return Err(JsonxfError::UnterminatedString {
+                offset: self.string_start,
+            });
+        }
+        if self.strict && self.depth > 0 {
+            return Err(JsonxfError::UnexpectedEnd {
+                depth: self.depth,
+                offset: self.offset,
+            });
+        }
+        if self.comment == Comment::Slash {
+            self.format_value_byte(C_SLASH, writer)?;
+        }
+        if self.comment == Comment::Line && self.keep_comments() {
+            self.end_line_comment();
+        }
+        self.comment = Comment::None;
+        if self.utf8_len > 0 {
+            writer.write_all(&self.utf8_buf[..self.utf8_len])?;
+            self.utf8_len = 0;
+        }
+        if self.in_string && self.in_backslash && self.slow_strings() {
+            writer.write_all(&[C_BACKSLASH])?;
+            self.in_backslash = false;
+        }
+        if self.escape_len > 0 {
+            let len = self.escape_len;
+            self.escape_len = 0;
+            self.write_escapes(len, writer)?;
+        }
+        if self.in_string && self.colors.is_some() {
+            // Don't leave the terminal colored after an unterminated string
+            writer.write_all(COLOR_RESET)?;
+        }
+        if self.in_scalar {
+            self.end_scalar(writer)?;
+        }
+        if self.pending_comma {
+            self.pending_comma = false;
+            self.write_punctuation(C_COMMA, writer)?;
+        }
+        if !self.marks.is_empty() {
+            self.write_comments(writer)?;
+        }
+        if self.pending == Pending::LineEnd && self.eager_record_separators && self.depth == 0 {
+            writer.write_all(self.record_separator.as_bytes())?;
+        }
+        self.pending = Pending::Nothing;
+        writer.write_all(self.trailing_output.as_bytes())?;
+        Ok(())
+    }
+
+    // Formats the start of a byte order mark held back from earlier
+    // buffers, once it turns out to be something else.
+    fn replay_bom_prefix(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let held = self.offset;
+        self.offset = 0;
+        self.format_chunk(&UTF8_BOM[..held], writer)
+    }
+
+    // Formats a complete input through an in-memory tree.  The input
+    // is minimized first, applying every option that works on the
+    // input (comment stripping and so on), then the tree is rearranged
+    // and written out with this formatter's whitespace settings.
+    fn format_tree(&mut self, input: &[u8], writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let minimized = self.minimize_for_tree(input)?;
+        let mut tree = Tree::parse(&minimized);
+        self.rearrange(&mut tree);
+        if self.preserve_bom && input.starts_with(&UTF8_BOM) {
+            writer.write_all(&UTF8_BOM)?;
+        }
+        let roots = tree.roots.clone();
+        self.write_tree(&tree, &roots, writer)
+    }
+
+    // Applies the options which change the members of objects.
+    fn rearrange(&self, tree: &mut Tree) {
+        match self.duplicate_keys {
+            DuplicateKeys::Keep => {}
+            DuplicateKeys::First => tree.remove_duplicate_keys(false),
+            DuplicateKeys::Last => tree.remove_duplicate_keys(true),
+        }
+        if !self.redact_keys.is_empty() {
+            tree.redact(&self.redact_keys, self.redact_non_strings);
+        }
+        if self.sort_keys {
+            tree.sort_keys();
+        }
+    }
+
+    // Minimizes input for `Tree::parse`.  UTF-16 input is decoded if
+    // `decode_utf16` is set; input decoded already, which has no UTF-16
+    // byte order mark, is left as it is.
+    fn minimize_for_tree(&self, input: &[u8]) -> Result<Vec<u8>, JsonxfError> {
+        let mut pre = self.clone();
+        pre.reset();
+        pre.sort_keys = false;
+        pre.max_inline_length = None;
+        pre.wrap_width = None;
+        pre.align_values = false;
+        pre.duplicate_keys = DuplicateKeys::Keep;
+        pre.redact_keys = vec![];
+        if pre.add_trailing_commas {
+            pre.add_trailing_commas = false;
+            pre.remove_trailing_commas = true;
+        }
+        if pre.comment_mode == CommentMode::Preserve {
+            pre.comment_mode = CommentMode::Strip;
+        }
+        pre.max_output_bytes = None;
+        pre.indent = String::from("");
+        pre.depth_indents = vec![];
+        pre.detect_indent = false;
+        pre.array_indent = None;
+        pre.object_indent = None;
+        pre.base_indent_level = 0;
+        pre.max_indent_level = None;
+        pre.expand_to_depth = None;
+        pre.line_separator = String::from("");
+        pre.record_separator = String::from("\n");
+        pre.before_colon = String::from("");
+        pre.bracket_padding = String::from("");
+        pre.after_array_open = None;
+        pre.before_array_close = None;
+        pre.after_object_open = None;
+        pre.before_object_close = None;
+        pre.after_comma = String::from("");
+        pre.after_colon = String::from("");
+        pre.trailing_output = String::from("");
+        pre.eager_record_separators = false;
+        pre.flush_after_documents = false;
+        pre.preserve_bom = false;
+        pre.colors = None;
+        let mut minimized: Vec<u8> = Vec::with_capacity(input.len());
+        pre.format_buf(input, &mut minimized)?;
+        pre.finish(&mut minimized)?;
+        Ok(minimized)
+    }
+
+    // Writes out the nodes at `roots` with this formatter's whitespace
+    // settings.
+    fn write_tree(
+        &mut self,
+        tree: &Tree,
+        roots: &[usize],
+        writer: &mut impl Write,
+    ) -> Result<(), JsonxfError> {
+        let colon = self.before_colon.chars().count() + 1 + self.after_colon.chars().count();
+        let inline = if let Some(width) = self.wrap_width {
+            let max_level = self.max_indent_level.unwrap_or(usize::MAX);
+            let indent_width = |b: u8, depth: usize| {
+                if self.base_indent_level + depth >= max_level {
+                    return 0;
+                }
+                let indent = match b {
+                    C_LEFT_BRACKET => self.array_indent.as_ref(),
+                    _ => self.object_indent.as_ref(),
+                };
+                indent
+                    .map_or(self.indent(), |indent| indent)
+                    .chars()
+                    .count()
+            };
+            let base = self.base_indent_level.min(max_level) * self.indent().chars().count();
+            tree.fits_width(width, self.max_inline_length, colon, base, indent_width)
+        } else if let Some(max) = self.max_inline_length {
+            tree.fits_inline(max, colon)
+        } else {
+            vec![]
+        };
+        let key_widths = if self.align_values && !self.line_separator.is_empty() {
+            tree.key_widths()
+        } else {
+            vec![]
+        };
+
+        let mut output: Vec<u8> = vec![];
+        let mut inline_starts: Vec<usize> = vec![];
+        let mut colon_pads: Vec<ColonPad> = vec![];
+        for (i, &root) in roots.iter().enumerate() {
+            tree.write_compact(
+                root,
+                &mut output,
+                &inline,
+                &key_widths,
+                &mut inline_starts,
+                &mut colon_pads,
+            );
+            // A newline after a string left open would become part of it
+            if !(tree.open_string && i + 1 == roots.len()) {
+                output.push(C_LF);
+            }
+        }
+
+        let mut post = self.clone();
+        post.reset();
+        post.indent = String::from(self.indent());
+        post.detect_indent = false;
+        post.sort_keys = false;
+        post.comment_mode = CommentMode::Unrecognized;
+        post.remove_trailing_commas = false;
+        post.max_inline_length = None;
+        post.wrap_width = None;
+        post.align_values = false;
+        post.duplicate_keys = DuplicateKeys::Keep;
+        post.redact_keys = vec![];
+        post.max_output_bytes = None;
+        post.inline_starts = inline_starts;
+        post.colon_pads = colon_pads;
+        post.format_chunk(&output, writer)?;
+        post.finish_chunks(writer)?;
+        self.stats = post.stats;
+        Ok(())
+    }
+
+    // Handles the start or first byte of a string, number, or literal.
+    fn format_value_byte(&mut self, b: u8, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let b = if !self.in_scalar && (b == C_QUOTE || (b == C_APOSTROPHE && self.single_quotes)) {
+            self.quote = b;
+            C_QUOTE
+        } else {
+            b
+        };
+        if !self.in_scalar {
+            // Comments which aren't being stripped look like scalars
+            let scalar = b != C_QUOTE && b != C_SLASH;
+            if self.after_scalar && scalar && self.depth > 0 {
+                // Keep it apart from the number or literal before it
+                writer.write_all(&[C_SPACE])?;
+            }
+            self.after_scalar = scalar;
+        }
+        if self.empty {
+            self.start_first_item(writer)?;
+            self.empty = false;
+        } else if self.depth == 0 && !self.in_scalar {
+            // A string, number, or literal at the top level
+            if self.first {
+                self.first = false;
+            } else if !self.eager_record_separators {
+                writer.write_all(self.record_separator.as_bytes())?;
+            }
+            self.stats.documents += 1;
+            if self.base_indent_level > 0 || !self.depth_indents.is_empty() {
+                self.write_indentation(writer)?;
+            }
+        }
+        if let Some(ref colors) = self.colors {
+            if !self.in_scalar {
+                let color = match b {
+                    _ if self.expect_key && (b == C_QUOTE || self.quote_keys) => &colors.key,
+                    C_QUOTE => &colors.string,
+                    b't' | b'f' | b'n' => &colors.literal,
+                    _ => &colors.number,
+                };
+                writer.write_all(color.as_bytes())?;
+            }
+        }
+        if !self.in_scalar {
+            self.in_key = self.expect_key;
+        }
+        if b == C_QUOTE {
+            self.string_chars = 0;
+        }
+        if b == C_QUOTE && !self.expect_key {
+            self.stats.strings += 1;
+        }
+        self.expect_key = false;
+        if b == C_QUOTE {
+            self.in_string = true;
+        } else {
+            if !self.in_scalar && self.in_key && self.quote_keys {
+                writer.write_all(&[C_QUOTE])?;
+            }
+            self.in_scalar = true;
+            if self.holds_scalars() {
+                // Held back until the whole number has been seen
+                self.scalar_buf.push(b);
+                return Ok(());
+            }
+        }
+        writer.write_all(&[b])?;
+        Ok(())
+    }
+
+    // Writes a bracket, comma or colon.
+    fn write_punctuation(&mut self, b: u8, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        self.after_scalar = false;
+        match self.colors {
+            Some(ref colors) => {
+                writer.write_all(colors.punctuation.as_bytes())?;
+                writer.write_all(&[b])?;
+                writer.write_all(COLOR_RESET)?;
+            }
+            None => writer.write_all(&[b])?,
+        }
+        Ok(())
+    }
+
+    fn write_comma(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        self.write_punctuation(C_COMMA, writer)?;
+        if self.inline_depth.is_some() {
+            if !self.inline_minimized {
+                writer.write_all(&[C_SPACE])?;
+            }
+            return Ok(());
+        }
+        writer.write_all(self.after_comma.as_bytes())?;
+        if self.keep_comments() {
+            // Leave room for a comment at the end of this line
+            self.pending = Pending::Newline;
+            return Ok(());
+        }
+        self.write_newline(writer)
+    }
+
+    // Called before the first element of an array or object.
+    fn start_first_item(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.inline_minimized {
+            return Ok(());
+        }
+        if self.on_one_line() {
+            let padding = match self.structure.last() {
+                Some(&C_LEFT_BRACE) => self.after_object_open.as_ref(),
+                _ => self.after_array_open.as_ref(),
+            };
+            writer.write_all(padding.unwrap_or(&self.bracket_padding).as_bytes())?;
+        }
+        self.write_newline(writer)
+    }
+
+    // Whether the current array or object is written without line
+    // breaks, so that bracket padding applies to it.
+    fn on_one_line(&self) -> bool {
+        self.line_separator.is_empty() || self.inline_depth.is_some()
+    }
+
+    // Starts a new line inside an array or object, unless it is being
+    // written on one line.
+    fn write_newline(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.inline_depth.is_some() {
+            return Ok(());
+        }
+        writer.write_all(self.line_separator.as_bytes())?;
+        self.write_indentation(writer)
+    }
+
+    // Writes the indentation for the current depth.
+    fn write_indentation(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let base = self.base_indent_level;
+        let depth = (self.depth + base).min(self.max_indent_level.unwrap_or(usize::MAX));
+        let mut levels = depth;
+        if let Some(last) = self.depth_indents.len().checked_sub(1) {
+            let n = depth.min(last);
+            writer.write_all(self.depth_indents[n].as_bytes())?;
+            levels -= n;
+        }
+        if self.array_indent.is_some() || self.object_indent.is_some() {
+            // Each level is indented according to the bracket opening it
+            for i in depth - levels..depth {
+                let opened = i.checked_sub(base).and_then(|i| self.structure.get(i));
+                let indent = match opened {
+                    Some(&C_LEFT_BRACKET) => self.array_indent.as_ref(),
+                    Some(_) => self.object_indent.as_ref(),
+                    None => None,
+                };
+                let indent = indent.map_or(self.indent(), |indent| indent);
+                writer.write_all(indent.as_bytes())?;
+            }
+            return Ok(());
+        }
+        // Write all the levels in one go from repeated copies of indent
+        let indent = self.seen_indent.as_ref().unwrap_or(&self.indent);
+        let length = levels * indent.len();
+        if length == 0 {
+            return Ok(());
+        }
+        while self.indent_buf.len() < length {
+            self.indent_buf.extend_from_slice(indent.as_bytes());
+        }
+        writer.write_all(&self.indent_buf[..length])?;
+        Ok(())
+    }
+
+    // Do the options in use need each number or literal held back
+    // until it ends?
+    fn holds_scalars(&self) -> bool {
+        self.normalize_numbers || self.nonfinite_to_null || self.validate_numbers
+    }
+
+    // Do the options in use need to look at each byte of a string?
+    fn slow_strings(&self) -> bool {
+        self.ascii_only
+            || self.unescape_unicode
+            || self.escape_slashes
+            || self.escape_html
+            || self.remove_needless_escapes
+            || self.hex_case != HexCase::Keep
+            || self.canonicalize_string_whitespace
+            || self.strict
+            || self.quote == C_APOSTROPHE
+    }
+
+    // Copies string contents for the options which need to look at
+    // each byte.  `start` is the offset of `buf` in the input.  Returns
+    // the number of bytes consumed, stopping after the closing quote.
+    fn format_string(
+        &mut self,
+        buf: &[u8],
+        start: usize,
+        writer: &mut impl Write,
+    ) -> Result<usize, JsonxfError> {
+        let quote = self.quote;
+        let slash = if self.escape_slashes { b'/' } else { C_QUOTE };
+        let html = self.escape_html;
+        let mut n = 0;
+        while n < buf.len() {
+            if self.utf8_len == 0 && !self.in_backslash && self.escape_len == 0 {
+                // Copy runs of ordinary characters in one write
+                let run = buf[n..]
+                    .iter()
+                    .position(|&c| {
+                        c == C_QUOTE
+                            || c == C_BACKSLASH
+                            || c == quote
+                            || c == slash
+                            || (html && (c == b'<' || c == b'>' || c == b'&'))
+                            || !(0x20..0x80).contains(&c)
+                    })
+                    .unwrap_or(buf.len() - n);
+                writer.write_all(&buf[n..n + run])?;
+                self.string_chars += run;
+                n += run;
+                if n == buf.len() {
+                    break;
+                }
+            }
+
+            let b = buf[n];
+            if self.strict {
+                self.check_string_byte(b, start + n)?;
+            }
+            n += 1;
+            if b & 0xC0 != 0x80 {
+                self.string_chars += 1;
+            }
+            if self.format_string_byte(b, writer)? {
+                return Ok(n);
+            }
+        }
+        Ok(n)
+    }
+
+    // In strict mode, reports a control character or invalid UTF-8 at
+    // byte `offset` of the input.
+    fn check_string_byte(&mut self, b: u8, offset: usize) -> Result<(), JsonxfError> {
+        if b < 0x20 && !self.canonicalize_string_whitespace {
+            return Err(JsonxfError::ControlCharacter { byte: b, offset });
+        }
+        if self.utf8_len > 0 {
+            if b & 0xC0 != 0x80 {
+                return Err(JsonxfError::InvalidUtf8 {
+                    offset: self.utf8_start,
+                });
+            }
+            if self.utf8_len + 1 == self.utf8_need {
+                // Rule out overlong forms, surrogates and the like
+                let mut bytes = self.utf8_buf;
+                bytes[self.utf8_len] = b;
+                if std::str::from_utf8(&bytes[..self.utf8_need]).is_err() {
+                    return Err(JsonxfError::InvalidUtf8 {
+                        offset: self.utf8_start,
+                    });
+                }
+            }
+        } else if b >= 0x80 {
+            if utf8_length(b).is_none() {
+                return Err(JsonxfError::InvalidUtf8 { offset });
+            }
+            self.utf8_start = offset;
+        }
+        Ok(())
+    }
+
+    // Handles one byte of a string in `format_string`.  Backslashes
+    // are held back until the following byte shows whether they begin
+    // a `\u` escape.  Returns true after the closing quote.
+    fn format_string_byte(&mut self, b: u8, writer: &mut impl Write) -> Result<bool, JsonxfError> {
+        if self.escape_len > 0 {
+            return self.format_escape_byte(b, writer);
+        }
+
+        if self.utf8_len > 0 {
+            if b & 0xC0 == 0x80 {
+                self.utf8_buf[self.utf8_len] = b;
+                self.utf8_len += 1;
+                if self.utf8_len == self.utf8_need {
+                    self.write_utf8_char(writer)?;
+                }
+                return Ok(false);
+            }
+            // Not valid UTF-8; pass it through
+            writer.write_all(&self.utf8_buf[..self.utf8_len])?;
+            self.utf8_len = 0;
+        }
+
+        if self.in_backslash {
+            self.in_backslash = false;
+            if b == C_APOSTROPHE && self.quote == C_APOSTROPHE {
+                // No need to escape it in double quotes
+                writer.write_all(&[b])?;
+            } else if b == b'/' && self.remove_needless_escapes && !self.escape_slashes {
+                writer.write_all(&[b])?;
+                self.string_chars -= 1;
+            } else if b == b'u' && (self.decodes_escapes() || self.hex_case != HexCase::Keep) {
+                self.escape_buf[0] = C_BACKSLASH;
+                self.escape_buf[1] = b;
+                self.escape_len = 2;
+            } else {
+                writer.write_all(&[C_BACKSLASH, b])?;
+            }
+            return Ok(false);
+        }
+
+        match b {
+            C_QUOTE if self.quote == C_APOSTROPHE => {
+                writer.write_all(&[C_BACKSLASH, C_QUOTE])?;
+            }
+            C_QUOTE | C_APOSTROPHE if b == self.quote => {
+                writer.write_all(&[C_QUOTE])?;
+                // It was counted in format_string
+                self.string_chars -= 1;
+                self.end_string(writer)?;
+                return Ok(true);
+            }
+            C_BACKSLASH => {
+                self.in_backslash = true;
+            }
+            b'/' if self.escape_slashes => {
+                writer.write_all(b"\\/")?;
+                self.string_chars += 1;
+            }
+            b'<' | b'>' | b'&' if self.escape_html => {
+                let hex = self.hex_digits();
+                let (high, low) = (hex[(b >> 4) as usize], hex[(b & 0xF) as usize]);
+                writer.write_all(&[C_BACKSLASH, b'u', b'0', b'0', high, low])?;
+                self.string_chars += 5;
+            }
+            0..=0x1F if self.canonicalize_string_whitespace => {
+                let escape = match b {
+                    0x08 => b'b',
+                    0x0C => b'f',
+                    C_LF => b'n',
+                    C_CR => b'r',
+                    C_TAB => b't',
+                    _ => 0,
+                };
+                if escape == 0 {
+                    let hex = self.hex_digits();
+                    let (high, low) = (hex[(b >> 4) as usize], hex[(b & 0xF) as usize]);
+                    writer.write_all(&[C_BACKSLASH, b'u', b'0', b'0', high, low])?;
+                    self.string_chars += 5;
+                } else {
+                    writer.write_all(&[C_BACKSLASH, escape])?;
+                    self.string_chars += 1;
+                }
+            }
+            _ => match utf8_length(b) {
+                Some(length) => {
+                    self.utf8_buf[0] = b;
+                    self.utf8_len = 1;
+                    self.utf8_need = length;
+                }
+                None => {
+                    writer.write_all(&[b])?;
+                }
+            },
+        }
+        Ok(false)
+    }
+
+    // Collects a `\uXXXX` escape, and the low surrogate following a
+    // high one, into `escape_buf`.
+    fn format_escape_byte(&mut self, b: u8, writer: &mut impl Write) -> Result<bool, JsonxfError> {
+        let len = self.escape_len;
+        let expected = match len {
+            6 => b == C_BACKSLASH,
+            7 => b == b'u',
+            _ => b.is_ascii_hexdigit(),
+        };
+        if !expected {
+            // Not an escape we can decode; pass through what was seen
+            // so far, keeping anything after a high surrogate's escape
+            // for another look.
+            let pending = self.escape_buf;
+            let kept = len.min(6);
+            self.escape_len = 0;
+            self.write_escapes(kept, writer)?;
+            for &c in pending[kept..len].iter() {
+                self.format_string_byte(c, writer)?;
+            }
+            return self.format_string_byte(b, writer);
+        }
+
+        self.escape_buf[len] = b;
+        self.escape_len += 1;
+        if self.escape_len == 6 {
+            if !self.decodes_escapes() {
+                // Only collected for hex_case
+                self.escape_len = 0;
+                return self.write_escapes(6, writer).map(|_| false);
+            }
+            let unit = parse_hex(&self.escape_buf[2..6]);
+            if (0xD800..0xDC00).contains(&unit) {
+                // Wait for the low surrogate
+                return Ok(false);
+            }
+            self.escape_len = 0;
+            match std::char::from_u32(unit) {
+                Some(c) => self.write_unescaped(c, 6, writer)?,
+                None => self.write_escapes(6, writer)?,
+            }
+        } else if self.escape_len == 12 {
+            let high = parse_hex(&self.escape_buf[2..6]);
+            let low = parse_hex(&self.escape_buf[8..12]);
+            self.escape_len = 0;
+            if (0xDC00..0xE000).contains(&low) {
+                let c = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                self.write_unescaped(std::char::from_u32(c).unwrap(), 12, writer)?;
+            } else {
+                // A lone high surrogate followed by another escape
+                let pending = self.escape_buf;
+                self.write_escapes(6, writer)?;
+                for &c in pending[6..12].iter() {
+                    self.format_string_byte(c, writer)?;
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    // Writes the first `len` bytes of `escape_buf`, changing the case
+    // of the hex digits of each complete `\uXXXX` escape for hex_case.
+    fn write_escapes(&mut self, len: usize, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let mut bytes = self.escape_buf;
+        for escape in bytes[..len - len % 6].chunks_mut(6) {
+            for digit in escape[2..].iter_mut() {
+                match self.hex_case {
+                    HexCase::Keep => {}
+                    HexCase::Lower => digit.make_ascii_lowercase(),
+                    HexCase::Upper => digit.make_ascii_uppercase(),
+                }
+            }
+        }
+        writer.write_all(&bytes[..len])?;
+        Ok(())
+    }
+
+    // Are `\uXXXX` escapes decoded where they don't need to be kept?
+    fn decodes_escapes(&self) -> bool {
+        self.unescape_unicode || self.remove_needless_escapes
+    }
+
+    // The digits for escapes written by the formatter itself.
+    fn hex_digits(&self) -> &'static [u8; 16] {
+        match self.hex_case {
+            HexCase::Upper => b"0123456789ABCDEF",
+            _ => b"0123456789abcdef",
+        }
+    }
+
+    // Writes the character decoded from the first `len` bytes of
+    // `escape_buf`, or keeps the escape if it is still needed.
+    fn write_unescaped(
+        &mut self,
+        c: char,
+        len: usize,
+        writer: &mut impl Write,
+    ) -> Result<(), JsonxfError> {
+        let keep = c < ' '
+            || c == '"'
+            || c == '\\'
+            || (self.ascii_only && !c.is_ascii())
+            || (self.escape_html && (c == '<' || c == '>' || c == '&'));
+        if keep {
+            self.write_escapes(len, writer)?;
+        } else if c == '/' && self.escape_slashes {
+            writer.write_all(b"\\/")?;
+            self.string_chars -= len - 2;
+        } else {
+            let mut bytes = [0_u8; 4];
+            writer.write_all(c.encode_utf8(&mut bytes).as_bytes())?;
+            self.string_chars -= len - 1;
+        }
+        Ok(())
+    }
+
+    // Writes the character collected in `utf8_buf`.
+    fn write_utf8_char(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let bytes = &self.utf8_buf[..self.utf8_len];
+        self.utf8_len = 0;
+        let c = match std::str::from_utf8(bytes) {
+            Ok(s) => s.chars().next().unwrap(),
+            Err(_) => {
+                writer.write_all(bytes)?;
+                return Ok(());
+            }
+        };
+        if self.ascii_only {
+            let mut units = [0_u16; 2];
+            for &unit in c.encode_utf16(&mut units).iter() {
+                let hex = self.hex_digits();
+                writer.write_all(&[
+                    C_BACKSLASH,
+                    b'u',
+                    hex[(unit >> 12) as usize & 0xF],
+                    hex[(unit >> 8) as usize & 0xF],
+                    hex[(unit >> 4) as usize & 0xF],
+                    hex[unit as usize & 0xF],
+                ])?;
+            }
+        } else {
+            writer.write_all(bytes)?;
+        }
+        Ok(())
+    }
+
+    // Called when a top-level value ends.
+    fn end_document(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if self.eager_record_separators {
+            writer.write_all(self.record_separator.as_bytes())?;
+        }
+        if self.flush_after_documents {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn end_string(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        self.in_string = false;
+        if !self.in_key {
+            self.stats.string_chars += self.string_chars;
+            self.stats.longest_string = self.stats.longest_string.max(self.string_chars);
+        }
+        if self.colors.is_some() {
+            writer.write_all(COLOR_RESET)?;
+        }
+        if self.depth == 0 {
+            self.end_document(writer)?;
+        }
+        Ok(())
+    }
+
+    // Handles a byte which may be part of a comment, returning whether
+    // it was.  `offset` is where it is in the input.
+    fn format_comment_byte(
+        &mut self,
+        b: u8,
+        offset: usize,
+        writer: &mut impl Write,
+    ) -> Result<bool, JsonxfError> {
+        match self.comment {
+            Comment::None => {
+                if b == C_SLASH {
+                    self.comment = Comment::Slash;
+                    return Ok(true);
+                }
+                Ok(false)
+            }
+            Comment::Slash => {
+                if b == C_SLASH {
+                    self.comment = Comment::Line;
+                } else if b == C_STAR {
+                    self.comment = Comment::Block;
+                } else {
+                    // Not a comment after all
+                    self.comment = Comment::None;
+                    self.format_value_byte(C_SLASH, writer)?;
+                    return Ok(false);
+                }
+                if self.comment_mode == CommentMode::Reject {
+                    return Err(JsonxfError::Comment { offset: offset - 1 });
+                }
+                if self.in_scalar {
+                    self.end_scalar(writer)?;
+                }
+                if self.keep_comments() {
+                    self.marks.push(CommentMark {
+                        start: self.comments.len(),
+                        own_line: self.line_start,
+                        line: b == C_SLASH,
+                        newline_after: false,
+                    });
+                    self.comments.extend_from_slice(&[C_SLASH, b]);
+                }
+                Ok(true)
+            }
+            Comment::Line => {
+                if b == C_LF {
+                    self.comment = Comment::None;
+                    if self.keep_comments() {
+                        self.end_line_comment();
+                    }
+                } else if self.keep_comments() {
+                    self.comments.push(b);
+                }
+                Ok(true)
+            }
+            Comment::Block => {
+                if b == C_STAR {
+                    self.comment = Comment::BlockStar;
+                }
+                if self.keep_comments() {
+                    self.comments.push(b);
+                }
+                Ok(true)
+            }
+            Comment::BlockStar => {
+                if b == C_SLASH {
+                    self.comment = Comment::None;
+                    self.line_start = false;
+                } else if b != C_STAR {
+                    self.comment = Comment::Block;
+                }
+                if self.keep_comments() {
+                    self.comments.push(b);
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    // Is `CommentMode::Preserve` in effect?
+    fn keep_comments(&self) -> bool {
+        self.comment_mode == CommentMode::Preserve
+    }
+
+    // Called at the newline ending a `//` comment, or the end of input.
+    fn end_line_comment(&mut self) {
+        let start = self.marks.last().map_or(0, |mark| mark.start);
+        while self.comments.len() > start && is_whitespace(self.comments[self.comments.len() - 1]) {
+            self.comments.pop();
+        }
+        self.line_start = true;
+    }
+
+    // With `CommentMode::Preserve`, writes the comments seen since the last
+    // token and any whitespace held back, before the token `b`.
+    fn before_token(&mut self, b: u8, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        if !self.marks.is_empty() {
+            self.write_comments(writer)?;
+        }
+        self.line_start = false;
+        self.spaced = match b {
+            C_COLON => !self.after_colon.is_empty(),
+            C_COMMA => !self.after_comma.is_empty(),
+            _ => false,
+        };
+        let pending = std::mem::replace(&mut self.pending, Pending::Nothing);
+        let hard = pending == Pending::LineEnd;
+        match b {
+            _ if pending == Pending::Nothing => {}
+            C_RIGHT_BRACKET | C_RIGHT_BRACE => {
+                // The closing bracket starts its own line, unless the
+                // array or object is empty apart from comments
+                if hard && self.line_separator.is_empty() {
+                    writer.write_all(&[C_LF])?;
+                }
+                if self.empty && pending >= Pending::Newline {
+                    self.empty = false;
+                }
+            }
+            _ if self.empty
+                || (self.depth == 0 && !self.first && !self.eager_record_separators) =>
+            {
+                // A new line or record separator is coming anyway
+                let separator = if self.empty {
+                    &self.line_separator
+                } else {
+                    &self.record_separator
+                };
+                if hard && !separator.contains('\n') {
+                    writer.write_all(&[C_LF])?;
+                }
+            }
+            _ if pending == Pending::Space => writer.write_all(&[C_SPACE])?,
+            _ => self.write_comment_break(hard, writer)?,
+        }
+        Ok(())
+    }
+
+    // Writes out the comments held in `comments`.
+    fn write_comments(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        let comments = std::mem::take(&mut self.comments);
+        let marks = std::mem::take(&mut self.marks);
+        for (i, mark) in marks.iter().enumerate() {
+            let end = marks.get(i + 1).map_or(comments.len(), |next| next.start);
+            let mut pending = std::mem::replace(&mut self.pending, Pending::Nothing);
+            if mark.own_line || (self.depth == 0 && self.eager_record_separators && !self.first) {
+                if pending >= Pending::Newline {
+                    self.write_comment_break(pending == Pending::LineEnd, writer)?;
+                    pending = Pending::Nothing;
+                } else if self.depth == 0 {
+                    if !self.first && !self.eager_record_separators {
+                        self.write_comment_break(true, writer)?;
                     }
+                } else if self.empty {
+                    self.start_first_item(writer)?;
+                    self.empty = false;
+                } else {
+                    self.write_newline(writer)?;
                 }
+            } else if pending == Pending::LineEnd {
+                self.write_comment_break(true, writer)?;
+                pending = Pending::Nothing;
+            } else if !self.spaced {
+                writer.write_all(&[C_SPACE])?;
+            }
+            writer.write_all(&comments[mark.start..end])?;
+            self.spaced = false;
+            self.pending = pending.max(Pending::Space);
+            if mark.line {
+                self.pending = Pending::LineEnd;
+            } else if mark.newline_after && self.pending < Pending::Newline {
+                self.pending = Pending::Newline;
+            }
+        }
+        self.comments = comments;
+        self.comments.clear();
+        self.marks = marks;
+        self.marks.clear();
+        Ok(())
+    }
+
+    // Starts a new line after a comment.  `hard` means it must really
+    // be a new line, even in minimized output.
+    fn write_comment_break(
+        &mut self,
+        hard: bool,
+        writer: &mut impl Write,
+    ) -> Result<(), JsonxfError> {
+        if hard && self.line_separator.is_empty() {
+            writer.write_all(&[C_LF])?;
+        }
+        self.write_newline(writer)
+    }
+
+    // Called on the first byte after a number or literal.
+    fn end_scalar(&mut self, writer: &mut impl Write) -> Result<(), JsonxfError> {
+        self.in_scalar = false;
+        if !self.scalar_buf.is_empty() {
+            let nonfinite = matches!(&self.scalar_buf[..], b"NaN" | b"Infinity" | b"-Infinity");
+            let numeric = matches!(self.scalar_buf[0], b'-' | b'+' | b'.' | b'0'..=b'9');
+            if self.validate_numbers
+                && numeric
+                && !(self.nonfinite_to_null && nonfinite)
+                && !is_json_number(&self.scalar_buf)
+            {
+                return Err(JsonxfError::InvalidNumber {
+                    offset: self.scalar_start,
+                });
+            }
+            let normalized = if self.normalize_numbers {
+                normalize_number(&self.scalar_buf)
             } else {
-                match b {
-                    C_SPACE | C_LF | C_CR | C_TAB => {
-                        // skip whitespace
-                    }
+                None
+            };
+            match normalized {
+                _ if self.nonfinite_to_null && nonfinite => writer.write_all(b"null")?,
+                Some(number) => writer.write_all(&number)?,
+                None => writer.write_all(&self.scalar_buf)?,
+            }
+            self.scalar_buf.clear();
+        }
+        if self.in_key && self.quote_keys {
+            writer.write_all(&[C_QUOTE])?;
+        }
+        if self.colors.is_some() {
+            writer.write_all(COLOR_RESET)?;
+        }
+        if self.depth == 0 {
+            self.end_document(writer)?;
+        }
+        Ok(())
+    }
+}
 
-                    C_LEFT_BRACKET | C_LEFT_BRACE => {
-                        if self.first {
-                            self.first = false;
-                            writer.write_all(&buf[n..n + 1])?;
-                        } else if self.empty {
-                            writer.write_all(self.line_separator.as_bytes())?;
-                            for _ in 0..self.depth {
-                                writer.write_all(self.indent.as_bytes())?;
-                            }
-                            writer.write_all(&buf[n..n + 1])?;
-                        } else if !self.eager_record_separators && self.depth == 0 {
-                            writer.write_all(self.record_separator.as_bytes())?;
-                            writer.write_all(&buf[n..n + 1])?;
-                        } else {
-                            writer.write_all(&buf[n..n + 1])?;
-                        }
-                        self.depth += 1;
-                        self.empty = true;
-                    }
+// Counts the characters in UTF-8 text, or what is taken for it.
+fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
 
-                    C_RIGHT_BRACKET | C_RIGHT_BRACE => {
-                        self.depth = self.depth.saturating_sub(1);
-                        if self.empty {
-                            self.empty = false;
-                            writer.write_all(&buf[n..n + 1])?;
-                        } else {
-                            writer.write_all(self.line_separator.as_bytes())?;
-                            for _ in 0..self.depth {
-                                writer.write_all(self.indent.as_bytes())?;
-                            }
-                            writer.write_all(&buf[n..n + 1])?;
-                        }
-                        if self.eager_record_separators && self.depth == 0 {
-                            writer.write_all(self.record_separator.as_bytes())?;
-                        }
-                    }
+// Returns the length of the UTF-8 sequence starting with this byte,
+// if it starts a multi-byte sequence.
+fn utf8_length(b: u8) -> Option<usize> {
+    match b {
+        0xC2..=0xDF => Some(2),
+        0xE0..=0xEF => Some(3),
+        0xF0..=0xF4 => Some(4),
+        _ => None,
+    }
+}
 
-                    C_COMMA => {
-                        writer.write_all(&buf[n..n + 1])?;
-                        writer.write_all(self.line_separator.as_bytes())?;
-                        for _ in 0..self.depth {
-                            writer.write_all(self.indent.as_bytes())?;
-                        }
-                    }
+// Returns the canonical form of a number, or `None` if `token` isn't
+// one.  A leading `+` is accepted.
+fn normalize_number(token: &[u8]) -> Option<Vec<u8>> {
+    fn digits(token: &[u8], start: usize) -> usize {
+        token[start..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+            + start
+    }
 
-                    C_COLON => {
-                        writer.write_all(&buf[n..n + 1])?;
-                        writer.write_all(self.after_colon.as_bytes())?;
-                    }
+    let mut n = 0;
+    let negative = token.first() == Some(&b'-');
+    if negative || token.first() == Some(&b'+') {
+        n += 1;
+    }
+    let int_start = n;
+    n = digits(token, n);
+    if n == int_start {
+        return None;
+    }
+    let int_end = n;
 
-                    _ => {
-                        if self.empty {
-                            writer.write_all(self.line_separator.as_bytes())?;
-                            for _ in 0..self.depth {
-                                writer.write_all(self.indent.as_bytes())?;
-                            }
-                            self.empty = false;
-                        }
-                        if b == C_QUOTE {
-                            self.in_string = true;
-                        }
-                        writer.write_all(&buf[n..n + 1])?;
-                    }
-                };
-            };
+    let mut frac = &token[0..0];
+    if token.get(n) == Some(&b'.') {
+        let start = n + 1;
+        n = digits(token, start);
+        if n == start {
+            return None;
+        }
+        frac = &token[start..n];
+    }
+
+    let mut exp_negative = false;
+    let mut exp = &token[0..0];
+    if token.get(n) == Some(&b'e') || token.get(n) == Some(&b'E') {
+        n += 1;
+        exp_negative = token.get(n) == Some(&b'-');
+        if exp_negative || token.get(n) == Some(&b'+') {
             n += 1;
         }
+        let start = n;
+        n = digits(token, start);
+        if n == start {
+            return None;
+        }
+        exp = &token[start..n];
+    }
+    if n != token.len() {
+        return None;
+    }
+
+    let mut number = Vec::with_capacity(token.len());
+    if negative {
+        number.push(b'-');
+    }
+    number.extend_from_slice(&token[int_start..int_end]);
+    let frac_len = frac.len() - frac.iter().rev().take_while(|&&b| b == b'0').count();
+    if frac_len > 0 {
+        number.push(b'.');
+        number.extend_from_slice(&frac[..frac_len]);
+    }
+    let exp_zeros = exp.iter().take_while(|&&b| b == b'0').count();
+    if exp_zeros < exp.len() {
+        number.push(b'e');
+        if exp_negative {
+            number.push(b'-');
+        }
+        number.extend_from_slice(&exp[exp_zeros..]);
+    }
+    Some(number)
+}
 
-        Ok(())
+// Checks `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, which is
+// stricter than what Rust's float parser accepts.
+fn is_json_number(bytes: &[u8]) -> bool {
+    let digits = |n: usize| bytes[n..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut n = 0;
+    if bytes.get(n) == Some(&b'-') {
+        n += 1;
+    }
+    match digits(n) {
+        0 => return false,
+        d if d > 1 && bytes[n] == b'0' => return false,
+        d => n += d,
     }
+    if bytes.get(n) == Some(&b'.') {
+        match digits(n + 1) {
+            0 => return false,
+            d => n += 1 + d,
+        }
+    }
+    if let Some(b'e') | Some(b'E') = bytes.get(n) {
+        n += 1;
+        if let Some(b'+') | Some(b'-') = bytes.get(n) {
+            n += 1;
+        }
+        match digits(n) {
+            0 => return false,
+            d => n += d,
+        }
+    }
+    n == bytes.len()
+}
+
+// Parses the hex digits of a `\uXXXX` escape.
+fn parse_hex(digits: &[u8]) -> u32 {
+    digits
+        .iter()
+        .fold(0, |acc, &d| acc * 16 + (d as char).to_digit(16).unwrap())
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, C_SPACE | C_LF | C_CR | C_TAB)
+}
+
+// Does this byte end a number or literal?
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        C_SPACE
+            | C_LF
+            | C_CR
+            | C_TAB
+            | C_COMMA
+            | C_COLON
+            | C_QUOTE
+            | C_LEFT_BRACE
+            | C_LEFT_BRACKET
+            | C_RIGHT_BRACE
+            | C_RIGHT_BRACKET
+    )
 }
 
 /// Pretty-prints a string of JSON-encoded data.
@@ -381,10 +3264,54 @@ impl Formatter {
 /// );
 /// ```
 ///
-pub fn pretty_print(json_string: &str) -> Result<String, String> {
+pub fn pretty_print(json_string: &str) -> Result<String, JsonxfError> {
     Formatter::pretty_printer().format(json_string)
 }
 
+/// Pretty-prints only the value which a JSON Pointer (RFC 6901)
+/// points to, using `indent` for indentation.  See
+/// [`Formatter::format_pointer`].
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::pretty_print_pointer("{\"a\":{\"b/c\":[1,2]}}", "/a/b~1c", "\t").unwrap(),
+///     "[\n\t1,\n\t2\n]"
+/// );
+/// ```
+///
+pub fn pretty_print_pointer(
+    json_string: &str,
+    pointer: &str,
+    indent: &str,
+) -> Result<String, JsonxfError> {
+    let mut xf = Formatter::pretty_printer();
+    xf.indent = String::from(indent);
+    xf.format_pointer(json_string, pointer)
+}
+
+/// Pretty-prints a byte slice of JSON-encoded data, without
+/// converting the output to a `String`.
+///
+/// The output will use two spaces as an indent, a line feed
+/// as newline character, and no trailing whitespace.
+/// To customize this behavior, use a
+/// `jsonxf::Formatter::pretty_printer()` directly.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::pretty_print_bytes(b"[\"\xff\"]").unwrap(),
+///     b"[\n  \"\xff\"\n]"
+/// );
+/// ```
+///
+pub fn pretty_print_bytes(json_bytes: &[u8]) -> Result<Vec<u8>, JsonxfError> {
+    Formatter::pretty_printer().format_bytes(json_bytes)
+}
+
 /// Pretty-prints a stream of JSON-encoded data.
 ///
 /// Input must be valid JSON data in UTF-8 encoding.
@@ -406,10 +3333,74 @@ pub fn pretty_print(json_string: &str) -> Result<String, String> {
 /// };
 /// ```
 ///
-pub fn pretty_print_stream(input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+#[cfg(feature = "std")]
+pub fn pretty_print_stream<R: Read, W: Write>(input: R, output: W) -> Result<(), JsonxfError> {
     Formatter::pretty_printer().format_stream(input, output)
 }
 
+/// Pretty-prints JSON-encoded data read from `input`, returning
+/// the output as a `String`.
+///
+/// The output is the same as `pretty_print_stream()` would write.
+///
+/// # Example:
+///
+/// ```
+/// let mut input: &[u8] = b"{\"a\":[1]}";
+/// assert_eq!(
+///     jsonxf::pretty_print_reader(&mut input).unwrap(),
+///     "{\n  \"a\": [\n    1\n  ]\n}"
+/// );
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn pretty_print_reader<R: Read>(input: R) -> Result<String, JsonxfError> {
+    Ok(String::from_utf8(pretty_print_reader_bytes(input)?)?)
+}
+
+/// Pretty-prints JSON-encoded data read from `input` like
+/// `pretty_print_reader()`, without converting the output to a
+/// `String`.
+///
+/// # Example:
+///
+/// ```
+/// let mut input: &[u8] = b"[\"\xff\"]";
+/// assert_eq!(
+///     jsonxf::pretty_print_reader_bytes(&mut input).unwrap(),
+///     b"[\n  \"\xff\"\n]"
+/// );
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn pretty_print_reader_bytes<R: Read>(input: R) -> Result<Vec<u8>, JsonxfError> {
+    let mut output: Vec<u8> = vec![];
+    Formatter::pretty_printer().format_stream(input, &mut output)?;
+    Ok(output)
+}
+
+/// Pretty-prints a stream of newline-delimited JSON, formatting each
+/// line of input as a separate document.
+///
+/// Documents are separated by a line feed, and blank lines are
+/// dropped.  To customize this behavior, use
+/// [`Formatter::format_ndjson_stream`] directly.
+///
+/// # Example:
+///
+/// ```no_run
+/// jsonxf::pretty_print_ndjson_stream(&mut std::io::stdin(), &mut std::io::stdout())
+///     .unwrap();
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn pretty_print_ndjson_stream<R: Read, W: Write>(
+    input: R,
+    output: W,
+) -> Result<(), JsonxfError> {
+    Formatter::pretty_printer().format_ndjson_stream(input, output)
+}
+
 /// Minimizes a string of JSON-encoded data.
 ///
 /// Input must be valid JSON data in UTF-8 encoding.
@@ -431,10 +3422,54 @@ pub fn pretty_print_stream(input: &mut dyn Read, output: &mut dyn Write) -> Resu
 /// );
 /// ```
 ///
-pub fn minimize(json_string: &str) -> Result<String, String> {
+pub fn minimize(json_string: &str) -> Result<String, JsonxfError> {
     Formatter::minimizer().format(json_string)
 }
 
+/// Minimizes a string of JSON-encoded data and sorts the members of
+/// every object by key, so that documents which differ only in
+/// whitespace and key order give the same result.
+///
+/// This is a practical canonical form for comparisons, not RFC 8785
+/// canonicalization: numbers and escapes in strings are left as they
+/// are.  For more control, use a `jsonxf::Formatter::minimizer()` with
+/// `sort_keys` and options such as `normalize_numbers` directly.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::normalize("{\"b\": [1, 2], \"a\": {\"d\": 4, \"c\": 3}}").unwrap(),
+///     jsonxf::normalize("{\"a\":{\"c\":3,\"d\":4},\n \"b\":[1,2]}").unwrap()
+/// );
+/// ```
+///
+pub fn normalize(json_string: &str) -> Result<String, JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    xf.sort_keys = true;
+    xf.format(json_string)
+}
+
+/// Minimizes a byte slice of JSON-encoded data, without
+/// converting the output to a `String`.
+///
+/// The output will use a line feed as newline character between
+/// records, and no trailing whitespace.  To customize this behavior,
+/// use a `jsonxf::Formatter::minimizer()` directly.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::minimize_bytes(b"[ \"\xff\" ]").unwrap(),
+///     b"[\"\xff\"]"
+/// );
+/// ```
+///
+pub fn minimize_bytes(json_bytes: &[u8]) -> Result<Vec<u8>, JsonxfError> {
+    Formatter::minimizer().format_bytes(json_bytes)
+}
+
 /// Minimizes a stream of JSON-encoded data.
 ///
 /// Input must be valid JSON data in UTF-8 encoding.
@@ -455,6 +3490,224 @@ pub fn minimize(json_string: &str) -> Result<String, String> {
 /// };
 /// ```
 ///
-pub fn minimize_stream(input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+#[cfg(feature = "std")]
+pub fn minimize_stream<R: Read, W: Write>(input: R, output: W) -> Result<(), JsonxfError> {
     Formatter::minimizer().format_stream(input, output)
 }
+
+/// Minimizes JSON-encoded data read from `input`, returning the
+/// output as a `String`.
+///
+/// The output is the same as `minimize_stream()` would write.
+///
+/// # Example:
+///
+/// ```
+/// let mut input: &[u8] = b"{ \"a\": [ 1 ] }";
+/// assert_eq!(jsonxf::minimize_reader(&mut input).unwrap(), "{\"a\":[1]}");
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn minimize_reader<R: Read>(input: R) -> Result<String, JsonxfError> {
+    Ok(String::from_utf8(minimize_reader_bytes(input)?)?)
+}
+
+/// Minimizes JSON-encoded data read from `input` like
+/// `minimize_reader()`, without converting the output to a `String`.
+///
+/// # Example:
+///
+/// ```
+/// let mut input: &[u8] = b"[ \"\xff\" ]";
+/// assert_eq!(jsonxf::minimize_reader_bytes(&mut input).unwrap(), b"[\"\xff\"]");
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn minimize_reader_bytes<R: Read>(input: R) -> Result<Vec<u8>, JsonxfError> {
+    let mut output: Vec<u8> = vec![];
+    Formatter::minimizer().format_stream(input, &mut output)?;
+    Ok(output)
+}
+
+/// Minimizes a stream of JSON-encoded data like `minimize_stream()`,
+/// returning figures such as the number of bytes read and written.
+///
+/// # Example:
+///
+/// ```
+/// let mut input: &[u8] = b"[ 1, 2, 3 ]";
+/// let mut output: Vec<u8> = vec![];
+/// let stats = jsonxf::minimize_stream_with_stats(&mut input, &mut output).unwrap();
+/// assert_eq!((stats.input_bytes, stats.output_bytes), (11, 7));
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn minimize_stream_with_stats<R: Read, W: Write>(
+    input: R,
+    output: W,
+) -> Result<Stats, JsonxfError> {
+    Formatter::minimizer().format_stream_with_stats(input, output)
+}
+
+/// Minimizes JSON-encoded data held in a `Vec<u8>`, overwriting it
+/// with the result instead of allocating a second buffer.
+///
+/// Minimizing almost always makes data shorter, so output is written
+/// over input which has already been read.  The rare exceptions, such
+/// as a record separator added between `[1][2]`, are inserted without
+/// losing any input.  This does the same job as `minimize_bytes()`, and
+/// is only meant for minimizing; it is not worth using to pretty-print.
+///
+/// If an error is returned, the contents of `json` are unspecified.
+///
+/// # Example:
+///
+/// ```
+/// let mut json = b"{ \"a\": [ 1, 2 ] }\n".to_vec();
+/// jsonxf::minimize_in_place(&mut json).unwrap();
+/// assert_eq!(json, b"{\"a\":[1,2]}");
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn minimize_in_place(json: &mut Vec<u8>) -> Result<(), JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    let mut chunk = [0_u8; BUF_SIZE];
+    let mut writer = InPlaceWriter {
+        buf: json,
+        read_pos: 0,
+        write_pos: 0,
+    };
+    while writer.read_pos < writer.buf.len() {
+        let n = BUF_SIZE.min(writer.buf.len() - writer.read_pos);
+        chunk[..n].copy_from_slice(&writer.buf[writer.read_pos..writer.read_pos + n]);
+        writer.read_pos += n;
+        xf.format_buf(&chunk[..n], &mut writer)?;
+    }
+    xf.finish(&mut writer)?;
+    let end = writer.write_pos;
+    json.truncate(end);
+    Ok(())
+}
+
+/// Formats the file at `path` with `formatter`, replacing its contents.
+///
+/// The output is written to a temporary file in the same directory,
+/// which is given the original file's permissions and then renamed
+/// over it, so the file is never left half-written.  If reading,
+/// formatting or writing fails, the original file is left as it was.
+///
+/// # Example:
+///
+/// ```no_run
+/// let mut fmt = jsonxf::Formatter::pretty_printer();
+/// jsonxf::format_file_in_place("config.json", &mut fmt).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn format_file_in_place<P: AsRef<Path>>(
+    path: P,
+    formatter: &mut Formatter,
+) -> Result<(), JsonxfError> {
+    let path = path.as_ref();
+    let input = fs::read(path)?;
+    let output = formatter.format_bytes(&input)?;
+
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(&output)?;
+        file.set_permissions(fs::metadata(path)?.permissions())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+/// Formats the file at `src` with `formatter`, writing the output to
+/// the file at `dst`, which is created or truncated.  The input is
+/// streamed through buffers of `formatter.buffer_capacity` bytes, so
+/// it is not held in memory unless the formatter's options require it.
+///
+/// If `src` and `dst` are the same file, this does the same as
+/// [`format_file_in_place`].  Otherwise, after an error `dst` may hold
+/// part of the output.
+///
+/// # Example:
+///
+/// ```no_run
+/// let mut fmt = jsonxf::Formatter::minimizer();
+/// fmt.buffer_capacity = 1024 * 1024;
+/// jsonxf::format_path_to_path("big.json", "big.min.json", &mut fmt).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn format_path_to_path<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    formatter: &mut Formatter,
+) -> Result<(), JsonxfError> {
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    let input = fs::File::open(src)?;
+    if let (Ok(a), Ok(b)) = (fs::canonicalize(src), fs::canonicalize(dst)) {
+        if a == b {
+            return format_file_in_place(src, formatter);
+        }
+    }
+    let output = fs::File::create(dst)?;
+    formatter.format_stream(input, output)
+}
+
+/// Checks that a stream of JSON-encoded data has matching brackets and
+/// closed strings, without writing any output.  The errors are those
+/// of `Formatter.strict`.  This is not full validation: `[1 2 x]`
+/// passes, for example.
+///
+/// # Example:
+///
+/// ```
+/// assert!(jsonxf::check(&b"{\"a\": [1, \"]\"]}"[..]).is_ok());
+/// assert_eq!(
+///     jsonxf::check(&b"{\"a\": [1]"[..]).unwrap_err().to_string(),
+///     "unexpected end of input at byte 9 with 1 unclosed array(s) or object(s)"
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn check<R: Read>(mut input: R) -> Result<(), JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    xf.format_stream_unbuffered(&mut input, &mut std::io::sink())
+}
+
+// Writes into the part of a buffer which has already been read,
+// shifting the unread part along if the output catches up with it.
+#[cfg(feature = "std")]
+struct InPlaceWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    read_pos: usize,
+    write_pos: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Write for InPlaceWriter<'a> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let room = bytes.len().min(self.read_pos - self.write_pos);
+        self.buf[self.write_pos..self.write_pos + room].copy_from_slice(&bytes[..room]);
+        let rest = &bytes[room..];
+        if !rest.is_empty() {
+            self.buf
+                .splice(self.read_pos..self.read_pos, rest.iter().cloned());
+            self.read_pos += rest.len();
+        }
+        self.write_pos += bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
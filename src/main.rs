@@ -11,6 +11,7 @@
 use std::{fs::File, io::ErrorKind};
 
 extern crate jsonxf;
+use jsonxf::JsonxfError;
 
 extern crate getopts;
 use getopts::Options;
@@ -58,6 +59,42 @@ fn do_main() -> Result<(), String> {
         "minimize",
         "minimize JSON instead of pretty-printing it",
     );
+    opts.optflag(
+        "c",
+        "strip-comments",
+        "remove // and /* */ comments from the input",
+    );
+    opts.optflag(
+        "P",
+        "preserve-comments",
+        "keep // and /* */ comments, re-indenting them with the JSON",
+    );
+    opts.optflag("S", "sort-keys", "sort the members of objects by key");
+    opts.optflag(
+        "a",
+        "ascii-output",
+        "escape non-ASCII characters in strings as \\uXXXX",
+    );
+    opts.optflag(
+        "u",
+        "unescape-unicode",
+        "decode \\uXXXX escapes in strings where possible",
+    );
+    opts.optflag(
+        "C",
+        "color",
+        "colorize pretty-printed output with ANSI escape codes",
+    );
+    opts.optflag(
+        "n",
+        "no-trailing-newline",
+        "do not end pretty-printed output with a newline",
+    );
+    opts.optflag(
+        "f",
+        "flush",
+        "flush output after each top-level value, for slow input such as `tail -f`",
+    );
     opts.optflag("h", "help", "print this message and exit");
 
     let matches = match opts.parse(&args[1..]) {
@@ -114,13 +151,8 @@ fn do_main() -> Result<(), String> {
             } else {
                 let mut temp_filename = String::from(&filename);
 
-                match input_filename {
-                    None => (),
-                    Some(f) => {
-                        if f == filename {
-                            temp_filename.push_str(".tmp");
-                        }
-                    }
+                if input_filename.as_ref() == Some(&filename) {
+                    temp_filename.push_str(".tmp");
                 }
 
                 output_filename = Some(String::from(&filename));
@@ -144,14 +176,35 @@ fn do_main() -> Result<(), String> {
         Some(string) => string,
     };
 
+    let comment_mode = if matches.opt_present("c") {
+        jsonxf::CommentMode::Strip
+    } else if matches.opt_present("P") {
+        jsonxf::CommentMode::Preserve
+    } else {
+        jsonxf::CommentMode::Unrecognized
+    };
+
     let result = if matches.opt_present("m") {
         let mut xf = jsonxf::Formatter::minimizer();
+        xf.comment_mode = comment_mode;
+        xf.sort_keys = matches.opt_present("S");
+        xf.ascii_only = matches.opt_present("a");
+        xf.unescape_unicode = matches.opt_present("u");
+        xf.flush_after_documents = matches.opt_present("f");
         xf.format_stream(&mut input, &mut output)
     } else {
         let mut xf = jsonxf::Formatter::pretty_printer();
         xf.indent = indent;
+        xf.comment_mode = comment_mode;
+        xf.sort_keys = matches.opt_present("S");
+        xf.ascii_only = matches.opt_present("a");
+        xf.unescape_unicode = matches.opt_present("u");
+        if matches.opt_present("C") {
+            xf.colors = Some(jsonxf::ColorScheme::default());
+        }
         // Ensure a trailing newline, as expected on Unix
-        xf.eager_record_separators = true;
+        xf.eager_record_separators = !matches.opt_present("n");
+        xf.flush_after_documents = matches.opt_present("f");
         xf.format_stream(&mut input, &mut output)
     };
 
@@ -161,7 +214,7 @@ fn do_main() -> Result<(), String> {
     }
 
     match result {
-        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        Err(JsonxfError::Io(ref e)) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
         Err(e) => Err(e.to_string()),
         Ok(_) => Ok(()),
     }
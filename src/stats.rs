@@ -0,0 +1,159 @@
+#[cfg(not(feature = "std"))]
+use io;
+#[cfg(not(feature = "std"))]
+use io::Write;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::io::prelude::*;
+
+/// Figures gathered while formatting, returned by
+/// `Formatter::format_stream_with_stats()` and similar functions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Bytes read from the input.
+    pub input_bytes: usize,
+
+    /// Bytes written to the output.
+    pub output_bytes: usize,
+
+    /// Number of top-level values, such as the records of a
+    /// concatenated stream.
+    pub documents: usize,
+
+    /// The deepest nesting of arrays and objects.
+    pub max_depth: usize,
+
+    /// Number of objects.
+    pub objects: usize,
+
+    /// Number of arrays.
+    pub arrays: usize,
+
+    /// Number of strings, not counting object keys.
+    pub strings: usize,
+
+    /// Number of characters (Unicode code points) in those strings,
+    /// leaving out the quotes.  An escape counts as the characters it
+    /// is written with, unless it is decoded by `unescape_unicode`.
+    pub string_chars: usize,
+
+    /// Length in characters of the longest of those strings.
+    pub longest_string: usize,
+}
+
+// Counts the bytes read through it.
+#[cfg(feature = "std")]
+pub struct CountingReader<R> {
+    pub inner: R,
+    pub count: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+// Calls `progress` with the number of bytes read so far each time
+// another `interval` of them has been read, and once more at the end
+// unless the last call had that number already.
+#[cfg(feature = "std")]
+pub struct ProgressReader<R, F> {
+    pub inner: R,
+    pub count: u64,
+    pub next: u64,
+    pub interval: u64,
+    pub progress: F,
+    pub reported: Option<u64>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        if (n == 0 && self.reported != Some(self.count)) || self.count >= self.next {
+            (self.progress)(self.count);
+            self.reported = Some(self.count);
+            self.next = self.count + self.interval;
+        }
+        Ok(n)
+    }
+}
+
+// Counts the bytes written through it.
+#[cfg(feature = "std")]
+pub struct CountingWriter<W> {
+    pub inner: W,
+    pub count: usize,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Passes on at most `remaining` bytes.  A write which goes past that
+// passes on what fits and fails, so that formatting stops there, and
+// sets `exceeded` for the caller to turn the failure into
+// `JsonxfError::OutputTooLarge`.
+pub struct LimitedWriter<'a, W: 'a + ?Sized> {
+    pub inner: &'a mut W,
+    pub remaining: usize,
+    pub exceeded: bool,
+}
+
+impl<'a, W: Write + ?Sized> LimitedWriter<'a, W> {
+    fn pass_on(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        let n = buf.len().min(self.remaining);
+        self.remaining -= n;
+        self.inner.write_all(&buf[..n])?;
+        if n < buf.len() {
+            self.exceeded = true;
+            return Err(limit_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[allow(clippy::io_other_error)] // io::Error::other needs Rust 1.74
+fn limit_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "output limit reached")
+}
+
+#[cfg(not(feature = "std"))]
+fn limit_error() -> io::Error {
+    io::Error::new("output limit reached")
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: Write + ?Sized> Write for LimitedWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pass_on(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, W: Write + ?Sized> Write for LimitedWriter<'a, W> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.pass_on(buf)
+    }
+}
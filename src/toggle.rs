@@ -0,0 +1,264 @@
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use super::{is_whitespace, Formatter, JsonxfError};
+
+/// Switches JSON between pretty-printed and minimized form: input
+/// which looks minimized is pretty-printed, and input which looks
+/// pretty-printed is minimized.  Handy for an editor command which
+/// toggles the formatting of a file.
+///
+/// # Example:
+///
+/// ```
+/// let mut toggle = jsonxf::Toggle::new();
+/// let pretty = toggle.format("{\"a\":[1]}").unwrap();
+/// assert_eq!(pretty, "{\n  \"a\": [\n    1\n  ]\n}");
+/// assert_eq!(toggle.format(&pretty).unwrap(), "{\"a\":[1]}");
+/// ```
+#[derive(Clone)]
+pub struct Toggle {
+    /// Used on input which looks minimized.  Defaults to
+    /// `Formatter::pretty_printer()`.
+    pub pretty_printer: Formatter,
+
+    /// Used on input which looks pretty-printed.  Defaults to
+    /// `Formatter::minimizer()`.
+    pub minimizer: Formatter,
+
+    /// How many bytes from the start of the input are passed to
+    /// `looks_pretty`.  Defaults to 1024.
+    pub sample_length: usize,
+
+    /// Decides whether a sample of input is pretty-printed.  Defaults
+    /// to [`looks_pretty`].
+    pub looks_pretty: fn(&[u8]) -> bool,
+}
+
+impl Toggle {
+    /// Returns a `Toggle` with the default settings.
+    pub fn new() -> Toggle {
+        Toggle {
+            pretty_printer: Formatter::pretty_printer(),
+            minimizer: Formatter::minimizer(),
+            sample_length: 1024,
+            looks_pretty,
+        }
+    }
+
+    /// Pretty-prints or minimizes a string of JSON-encoded data,
+    /// whichever it doesn't look like already.
+    pub fn format(&mut self, json_string: &str) -> Result<String, JsonxfError> {
+        let output = self.format_bytes(json_string.as_bytes())?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    /// Pretty-prints or minimizes a byte slice of JSON-encoded data,
+    /// whichever it doesn't look like already.
+    pub fn format_bytes(&mut self, json_bytes: &[u8]) -> Result<Vec<u8>, JsonxfError> {
+        let sample = &json_bytes[..json_bytes.len().min(self.sample_length)];
+        if (self.looks_pretty)(sample) {
+            self.minimizer.format_bytes(json_bytes)
+        } else {
+            self.pretty_printer.format_bytes(json_bytes)
+        }
+    }
+}
+
+impl Default for Toggle {
+    fn default() -> Toggle {
+        Toggle::new()
+    }
+}
+
+/// The default test used by [`Toggle`]: input looks pretty-printed if
+/// the first `[` or `{` outside a string is followed by a line break
+/// before anything else.
+///
+/// # Example:
+///
+/// ```
+/// assert!(jsonxf::looks_pretty(b"{\n  \"a\": 1\n}"));
+/// assert!(!jsonxf::looks_pretty(b"{\"a\":\n1}"));
+/// assert!(!jsonxf::looks_pretty(b"\"a\"\n"));
+/// ```
+pub fn looks_pretty(sample: &[u8]) -> bool {
+    let mut in_string = false;
+    let mut in_backslash = false;
+    let mut bytes = sample.iter();
+    for &b in bytes.by_ref() {
+        if in_backslash {
+            in_backslash = false;
+        } else if in_string {
+            match b {
+                b'\\' => in_backslash = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match b {
+                b'"' => in_string = true,
+                b'[' | b'{' => break,
+                _ => {}
+            }
+        }
+    }
+    for &b in bytes {
+        match b {
+            b'\n' => return true,
+            b' ' | b'\r' | b'\t' => {}
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Returns the indentation used by pretty-printed JSON: the
+/// whitespace starting the first line inside the first array or
+/// object which isn't empty, less that of the line the bracket is on.
+/// Returns None if there is no such line, as in minimized input.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(jsonxf::detect_indent(b"[]\n{\n    \"a\": 1\n}").unwrap(), "    ");
+/// assert_eq!(jsonxf::detect_indent(b"  [\n  \t1\n  ]").unwrap(), "\t");
+/// assert_eq!(jsonxf::detect_indent(b"{\"a\":[1]}"), None);
+/// ```
+pub fn detect_indent(sample: &[u8]) -> Option<String> {
+    match sample_indent(sample) {
+        IndentSample::Found(indent) => Some(indent),
+        _ => None,
+    }
+}
+
+// What the start of some input shows of its indentation.
+pub enum IndentSample {
+    Found(String),
+    NotIndented,
+    NeedMore,
+}
+
+// Looks for the indentation as in `detect_indent`, telling apart
+// input which isn't indented from input which ends too early to say.
+pub fn sample_indent(sample: &[u8]) -> IndentSample {
+    let mut in_string = false;
+    let mut in_backslash = false;
+    let mut line: Vec<u8> = vec![]; // spaces and tabs starting this line
+    let mut line_start = true;
+    let mut opened: Option<Vec<u8>> = None; // `line` at the open bracket
+    let mut new_line = false;
+    for &b in sample {
+        if in_backslash {
+            in_backslash = false;
+            continue;
+        } else if in_string {
+            match b {
+                b'\\' => in_backslash = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if b == b'\n' {
+            line.clear();
+            line_start = true;
+            new_line = true;
+            continue;
+        }
+        if b == b' ' || b == b'\t' {
+            if line_start {
+                line.push(b);
+            }
+            continue;
+        }
+        if is_whitespace(b) {
+            continue;
+        }
+        line_start = false;
+        if let Some(base) = opened.take() {
+            match b {
+                b']' | b'}' => {}
+                _ if !new_line || line.is_empty() => return IndentSample::NotIndented,
+                _ if line.starts_with(&base) && line.len() > base.len() => {
+                    return IndentSample::Found(
+                        String::from_utf8(line.split_off(base.len())).unwrap(),
+                    )
+                }
+                _ => return IndentSample::Found(String::from_utf8(line).unwrap()),
+            }
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                opened = Some(line.clone());
+                new_line = false;
+            }
+            _ => {}
+        }
+    }
+    IndentSample::NeedMore
+}
+
+/// Returns whether `Formatter::minimizer()` would leave this input
+/// as it is, going by whitespace alone: there is none outside of
+/// strings, except for a single line feed between top-level values.
+/// Stops at the first whitespace which would be removed, without
+/// producing any output.
+///
+/// # Example:
+///
+/// ```
+/// assert!(jsonxf::is_minimized("{\"a b\":[1,2]}\n[3]"));
+/// assert!(!jsonxf::is_minimized("{\"a\": 1}"));
+/// assert!(!jsonxf::is_minimized("[1]\n"));
+/// ```
+pub fn is_minimized(input: &str) -> bool {
+    let bytes = input.as_bytes();
+    let mut in_string = false;
+    let mut in_backslash = false;
+    let mut depth: usize = 0;
+    for (n, &b) in bytes.iter().enumerate() {
+        if in_backslash {
+            in_backslash = false;
+        } else if in_string {
+            match b {
+                b'\\' => in_backslash = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match b {
+                b'"' => in_string = true,
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                // The record separator, with nothing more around it
+                b'\n'
+                    if depth == 0
+                        && n > 0
+                        && n + 1 < bytes.len()
+                        && !is_whitespace(bytes[n - 1])
+                        && !is_whitespace(bytes[n + 1]) => {}
+                _ if is_whitespace(b) => return false,
+                _ => {}
+            }
+        }
+    }
+    true
+}
+
+/// Pretty-prints a string of JSON-encoded data if it looks minimized,
+/// or minimizes it if it looks pretty-printed.  To customize this
+/// behavior, use a `jsonxf::Toggle` directly.
+///
+/// # Example:
+///
+/// ```
+/// assert_eq!(
+///     jsonxf::reformat("[\n  1,\n  2\n]").unwrap(),
+///     "[1,2]"
+/// );
+/// ```
+pub fn reformat(json_string: &str) -> Result<String, JsonxfError> {
+    Toggle::new().format(json_string)
+}
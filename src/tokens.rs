@@ -0,0 +1,139 @@
+use std::ops::{ControlFlow, Range};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::{is_delimiter, is_whitespace};
+
+const C_COMMA: u8 = b',';
+const C_COLON: u8 = b':';
+const C_QUOTE: u8 = b'"';
+const C_BACKSLASH: u8 = b'\\';
+
+const C_LEFT_BRACE: u8 = b'{';
+const C_LEFT_BRACKET: u8 = b'[';
+const C_RIGHT_BRACE: u8 = b'}';
+const C_RIGHT_BRACKET: u8 = b']';
+
+/// A token passed to the callback of [`tokenize`].  Strings hold the
+/// text between the quotes, with escapes left as they are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    ObjectStart,
+    ObjectEnd,
+    ArrayStart,
+    ArrayEnd,
+    Comma,
+    Colon,
+    /// A string which is an object key.
+    Key(&'a str),
+    /// Any other string.
+    ValueString(&'a str),
+    /// A number, or anything else beginning with `-` or a digit.
+    ValueNumber(&'a str),
+    /// `true`, `false`, `null`, or anything else outside of strings.
+    ValueLiteral(&'a str),
+}
+
+/// Splits JSON-encoded data into tokens, calling `callback` with each
+/// one and its byte range in `input`, until the input ends or the
+/// callback returns `ControlFlow::Break`.  Returns whatever the last
+/// call returned.
+///
+/// Like the formatters, this does not validate its input: anything
+/// which isn't whitespace, a bracket, a comma, a colon or a string is
+/// passed on as a number or literal.  A string left open at the end
+/// of the input runs to the end of the input.
+///
+/// # Example:
+///
+/// ```
+/// use jsonxf::Token;
+/// use std::ops::ControlFlow;
+///
+/// let mut keys = vec![];
+/// jsonxf::tokenize("{\"a\": [1, {\"b\": null}]}", |token, range| {
+///     if let Token::Key(key) = token {
+///         keys.push((key, range));
+///     }
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(keys, vec![("a", 1..4), ("b", 11..14)]);
+/// ```
+pub fn tokenize<'a, F>(input: &'a str, mut callback: F) -> ControlFlow<()>
+where
+    F: FnMut(Token<'a>, Range<usize>) -> ControlFlow<()>,
+{
+    let bytes = input.as_bytes();
+    let mut structure: Vec<u8> = vec![];
+    let mut expect_key = false;
+    let mut n = 0;
+    while n < bytes.len() {
+        let b = bytes[n];
+        if is_whitespace(b) {
+            n += 1;
+            continue;
+        }
+        let start = n;
+        let token = match b {
+            C_LEFT_BRACE | C_LEFT_BRACKET => {
+                n += 1;
+                structure.push(b);
+                expect_key = b == C_LEFT_BRACE;
+                if b == C_LEFT_BRACE {
+                    Token::ObjectStart
+                } else {
+                    Token::ArrayStart
+                }
+            }
+            C_RIGHT_BRACE | C_RIGHT_BRACKET => {
+                n += 1;
+                structure.pop();
+                expect_key = false;
+                if b == C_RIGHT_BRACE {
+                    Token::ObjectEnd
+                } else {
+                    Token::ArrayEnd
+                }
+            }
+            C_COMMA => {
+                n += 1;
+                expect_key = structure.last() == Some(&C_LEFT_BRACE);
+                Token::Comma
+            }
+            C_COLON => {
+                n += 1;
+                expect_key = false;
+                Token::Colon
+            }
+            C_QUOTE => {
+                n += 1;
+                let mut in_backslash = false;
+                while n < bytes.len() && (in_backslash || bytes[n] != C_QUOTE) {
+                    in_backslash = !in_backslash && bytes[n] == C_BACKSLASH;
+                    n += 1;
+                }
+                let contents = &input[start + 1..n];
+                n = (n + 1).min(bytes.len());
+                if expect_key {
+                    Token::Key(contents)
+                } else {
+                    Token::ValueString(contents)
+                }
+            }
+            _ => {
+                while n < bytes.len() && !is_delimiter(bytes[n]) {
+                    n += 1;
+                }
+                let text = &input[start..n];
+                if b == b'-' || b.is_ascii_digit() {
+                    Token::ValueNumber(text)
+                } else {
+                    Token::ValueLiteral(text)
+                }
+            }
+        };
+        callback(token, start..n)?;
+    }
+    ControlFlow::Continue(())
+}
@@ -0,0 +1,551 @@
+// An in-memory representation of minimized JSON, used by the
+// formatting options which cannot be done in a single streaming pass.
+//
+// Nodes are stored in a flat arena in post-order (children before
+// their parents), so that no operation here needs to recurse and
+// deeply nested input cannot overflow the stack.
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeSet;
+
+use super::{is_delimiter, is_whitespace};
+use super::{C_BACKSLASH, C_COLON, C_COMMA, C_QUOTE};
+use super::{C_LEFT_BRACE, C_LEFT_BRACKET, C_RIGHT_BRACE, C_RIGHT_BRACKET};
+
+pub enum Node<'a> {
+    // A string, number, literal, or anything else that isn't structure
+    Scalar(&'a [u8]),
+    Array(Vec<usize>),
+    Object(Vec<Member>),
+}
+
+pub struct Member {
+    pub key: usize,
+    pub value: Option<usize>,
+}
+
+pub struct Tree<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub roots: Vec<usize>,
+    // Whether the input ended inside a string
+    pub open_string: bool,
+}
+
+// An array or object which has been opened but not yet closed.
+struct Frame {
+    is_object: bool,
+    items: Vec<usize>,
+    members: Vec<Member>,
+    key: Option<usize>,
+    after_colon: bool,
+}
+
+impl<'a> Tree<'a> {
+    // Parses minimized JSON, such as the output of
+    // `Formatter::minimizer()`.  Invalid input is accepted on a
+    // best-effort basis.
+    pub fn parse(input: &'a [u8]) -> Tree<'a> {
+        let mut tree = Tree {
+            nodes: vec![],
+            roots: vec![],
+            open_string: false,
+        };
+        let mut stack: Vec<Frame> = vec![];
+        let mut n = 0;
+        while n < input.len() {
+            let b = input[n];
+            match b {
+                C_LEFT_BRACE | C_LEFT_BRACKET => {
+                    stack.push(Frame {
+                        is_object: b == C_LEFT_BRACE,
+                        items: vec![],
+                        members: vec![],
+                        key: None,
+                        after_colon: false,
+                    });
+                    n += 1;
+                }
+                C_RIGHT_BRACE | C_RIGHT_BRACKET if !stack.is_empty() => {
+                    let frame = stack.pop().unwrap();
+                    tree.close(frame, &mut stack);
+                    n += 1;
+                }
+                C_COMMA | C_COLON if !stack.is_empty() => {
+                    let frame = stack.last_mut().unwrap();
+                    if frame.is_object {
+                        if b == C_COLON && frame.key.is_some() {
+                            frame.after_colon = true;
+                        } else if let Some(key) = frame.key.take() {
+                            frame.members.push(Member { key, value: None });
+                            frame.after_colon = false;
+                        }
+                    }
+                    n += 1;
+                }
+                C_QUOTE => {
+                    let end = string_end(input, n).unwrap_or_else(|| {
+                        tree.open_string = true;
+                        input.len()
+                    });
+                    tree.push_value(Node::Scalar(&input[n..end]), &mut stack);
+                    n = end;
+                }
+                _ if is_whitespace(b) => {
+                    n += 1;
+                }
+                _ => {
+                    let mut end = n + 1;
+                    while end < input.len() && !is_delimiter(input[end]) {
+                        end += 1;
+                    }
+                    tree.push_value(Node::Scalar(&input[n..end]), &mut stack);
+                    n = end;
+                }
+            }
+        }
+        while let Some(frame) = stack.pop() {
+            tree.close(frame, &mut stack);
+        }
+        tree
+    }
+
+    fn close(&mut self, mut frame: Frame, stack: &mut [Frame]) {
+        let node = if frame.is_object {
+            if let Some(key) = frame.key.take() {
+                frame.members.push(Member { key, value: None });
+            }
+            Node::Object(frame.members)
+        } else {
+            Node::Array(frame.items)
+        };
+        self.push_value(node, stack);
+    }
+
+    fn push_value(&mut self, node: Node<'a>, stack: &mut [Frame]) {
+        let index = self.nodes.len();
+        self.nodes.push(node);
+        match stack.last_mut() {
+            None => self.roots.push(index),
+            Some(frame) if !frame.is_object => frame.items.push(index),
+            Some(frame) => match frame.key {
+                None => frame.key = Some(index),
+                Some(key) if frame.after_colon => {
+                    frame.members.push(Member {
+                        key,
+                        value: Some(index),
+                    });
+                    frame.key = None;
+                    frame.after_colon = false;
+                }
+                Some(key) => {
+                    // A key with no colon or value
+                    frame.members.push(Member { key, value: None });
+                    frame.key = Some(index);
+                }
+            },
+        }
+    }
+
+    // Sorts the members of every object by the bytes of their keys.
+    // Members with equal keys keep their original order.
+    pub fn sort_keys(&mut self) {
+        for i in 0..self.nodes.len() {
+            let mut members = match self.nodes[i] {
+                Node::Object(ref mut members) => std::mem::take(members),
+                _ => continue,
+            };
+            members.sort_by(|a, b| self.key_bytes(a.key).cmp(self.key_bytes(b.key)));
+            self.nodes[i] = Node::Object(members);
+        }
+    }
+
+    // Leaves one member of every object for each key, either the last
+    // or the first.  Keys are compared after decoding escapes, where
+    // that is possible.
+    pub fn remove_duplicate_keys(&mut self, keep_last: bool) {
+        for i in 0..self.nodes.len() {
+            let mut members = match self.nodes[i] {
+                Node::Object(ref mut members) => std::mem::take(members),
+                _ => continue,
+            };
+            if keep_last {
+                members.reverse();
+            }
+            let mut seen: BTreeSet<Vec<u8>> = BTreeSet::new();
+            members.retain(|member| {
+                let bytes = self.key_bytes(member.key);
+                let key = match unescape(bytes) {
+                    Some(key) => key.into_bytes(),
+                    None => bytes.to_vec(),
+                };
+                seen.insert(key)
+            });
+            if keep_last {
+                members.reverse();
+            }
+            self.nodes[i] = Node::Object(members);
+        }
+    }
+
+    // Replaces the values of members whose decoded keys are in `keys`
+    // with the string `"***"`: only string values, unless `non_strings`.
+    pub fn redact(&mut self, keys: &[String], non_strings: bool) {
+        let mut redacted = vec![];
+        for node in self.nodes.iter() {
+            if let Node::Object(ref members) = *node {
+                for member in members {
+                    let value = match member.value {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    let is_string = match self.nodes[value] {
+                        Node::Scalar(bytes) => bytes.first() == Some(&C_QUOTE),
+                        _ => false,
+                    };
+                    let bytes = self.key_bytes(member.key);
+                    let key = match unescape(bytes) {
+                        Some(key) => key,
+                        None => continue,
+                    };
+                    if (is_string || non_strings) && keys.contains(&key) {
+                        redacted.push(value);
+                    }
+                }
+            }
+        }
+        for value in redacted {
+            self.nodes[value] = Node::Scalar(b"\"***\"");
+        }
+    }
+
+    // Lists each key repeated in an object under the node at `root`,
+    // with the JSON Pointer of the object, in document order.
+    pub fn duplicate_keys(&self, root: usize) -> Vec<(String, String)> {
+        let mut found = vec![];
+        let mut todo = vec![(root, String::new())];
+        while let Some((index, path)) = todo.pop() {
+            match self.nodes[index] {
+                Node::Scalar(_) => {}
+                Node::Array(ref items) => {
+                    for (i, &item) in items.iter().enumerate().rev() {
+                        todo.push((item, format!("{}/{}", path, i)));
+                    }
+                }
+                Node::Object(ref members) => {
+                    let keys: Vec<String> = members
+                        .iter()
+                        .map(|member| {
+                            let bytes = self.key_bytes(member.key);
+                            unescape(bytes)
+                                .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+                        })
+                        .collect();
+                    let mut seen: BTreeSet<&str> = BTreeSet::new();
+                    let mut repeated: BTreeSet<&str> = BTreeSet::new();
+                    for key in keys.iter() {
+                        if !seen.insert(key) && repeated.insert(key) {
+                            found.push((path.clone(), key.clone()));
+                        }
+                    }
+                    for (member, key) in members.iter().zip(keys.iter()).rev() {
+                        if let Some(value) = member.value {
+                            let token = key.replace('~', "~0").replace('/', "~1");
+                            todo.push((value, format!("{}/{}", path, token)));
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    // Follows a JSON Pointer from the node at `root`.
+    pub fn pointer(&self, root: usize, pointer: &str) -> Option<usize> {
+        if pointer.is_empty() {
+            return Some(root);
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+        let mut index = root;
+        for token in pointer[1..].split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            index = match self.nodes[index] {
+                Node::Scalar(_) => return None,
+                Node::Array(ref items) => {
+                    if token.len() > 1 && token.starts_with('0') {
+                        return None;
+                    }
+                    *items.get(token.parse::<usize>().ok()?)?
+                }
+                Node::Object(ref members) => {
+                    members
+                        .iter()
+                        .rev()
+                        .find(|member| {
+                            unescape(self.key_bytes(member.key)).as_deref() == Some(&token[..])
+                        })?
+                        .value?
+                }
+            };
+        }
+        Some(index)
+    }
+
+    // Returns the contents of a key, without quotes.
+    fn key_bytes(&self, index: usize) -> &'a [u8] {
+        match self.nodes[index] {
+            Node::Scalar(bytes) if bytes.len() >= 2 && bytes[0] == C_QUOTE => {
+                &bytes[1..bytes.len() - 1]
+            }
+            Node::Scalar(bytes) => bytes,
+            _ => &[],
+        }
+    }
+
+    // Decides which arrays and objects can be written on one line in
+    // at most `max` characters, with `, ` between elements and `colon`
+    // characters between keys and values.
+    pub fn fits_inline(&self, max: usize, colon: usize) -> Vec<bool> {
+        self.inline_lengths(colon)
+            .iter()
+            .map(|&length| length <= max)
+            .collect()
+    }
+
+    // Decides which arrays and objects to write on one line so that
+    // lines stay within `width` characters where they can.  Working
+    // down from the roots, a node goes on one line if it fits there
+    // after its indentation and key, leaving room for a comma after
+    // it, and is at most `max` characters long if that is given; if
+    // not, each of its children gets the same chance on a line of its
+    // own.  Roots start `base` characters in, `colon` is as for
+    // `fits_inline`, and `indent` gives the width of the indentation
+    // added inside an opening bracket at a given depth.
+    #[allow(clippy::unnecessary_map_or)] // is_none_or needs Rust 1.82
+    pub fn fits_width<F: Fn(u8, usize) -> usize>(
+        &self,
+        width: usize,
+        max: Option<usize>,
+        colon: usize,
+        base: usize,
+        indent: F,
+    ) -> Vec<bool> {
+        let lengths = self.inline_lengths(colon);
+        let mut inline = vec![false; self.nodes.len()];
+        // Each node with its depth, the indentation of its line, and the
+        // widths of what comes before and after it on that line
+        let mut todo: Vec<(usize, usize, usize, usize, usize)> = self
+            .roots
+            .iter()
+            .map(|&root| (root, 0, base, 0, 0))
+            .collect();
+        while let Some((index, depth, line_indent, before, after)) = todo.pop() {
+            let length = lengths[index];
+            if line_indent + before + length + after <= width
+                && max.map_or(true, |max| length <= max)
+            {
+                inline[index] = true;
+                continue;
+            }
+            match self.nodes[index] {
+                Node::Scalar(_) => {}
+                Node::Array(ref items) => {
+                    let inner = line_indent + indent(C_LEFT_BRACKET, depth);
+                    for (i, &item) in items.iter().enumerate() {
+                        let comma = if i + 1 < items.len() { 1 } else { 0 };
+                        todo.push((item, depth + 1, inner, 0, comma));
+                    }
+                }
+                Node::Object(ref members) => {
+                    let inner = line_indent + indent(C_LEFT_BRACE, depth);
+                    for (i, member) in members.iter().enumerate() {
+                        if let Some(value) = member.value {
+                            let comma = if i + 1 < members.len() { 1 } else { 0 };
+                            let key = lengths[member.key] + colon;
+                            todo.push((value, depth + 1, inner, key, comma));
+                        }
+                    }
+                }
+            }
+        }
+        inline
+    }
+
+    // Returns the length in characters of each node written on one
+    // line, with `, ` between elements and `colon` characters between
+    // keys and values.
+    fn inline_lengths(&self, colon: usize) -> Vec<usize> {
+        // Children come before their parents, so lengths can be
+        // worked out in one pass
+        let mut lengths: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter() {
+            let length = match *node {
+                Node::Scalar(bytes) => bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count(),
+                Node::Array(ref items) => {
+                    let sum: usize = items.iter().map(|&item| lengths[item]).sum();
+                    2 + sum + 2 * items.len().saturating_sub(1)
+                }
+                Node::Object(ref members) => {
+                    let sum: usize = members
+                        .iter()
+                        .map(|member| match member.value {
+                            Some(value) => lengths[member.key] + colon + lengths[value],
+                            None => lengths[member.key],
+                        })
+                        .sum();
+                    2 + sum + 2 * members.len().saturating_sub(1)
+                }
+            };
+            lengths.push(length);
+        }
+        lengths
+    }
+
+    // Returns the length in characters of the longest key of each
+    // object, or 0 for anything else.
+    pub fn key_widths(&self) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .map(|node| match *node {
+                Node::Object(ref members) => members
+                    .iter()
+                    .map(|member| self.width(member.key))
+                    .max()
+                    .unwrap_or(0),
+                _ => 0,
+            })
+            .collect()
+    }
+
+    // Returns the length in characters of a scalar.
+    fn width(&self, index: usize) -> usize {
+        match self.nodes[index] {
+            Node::Scalar(bytes) => bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count(),
+            _ => 0,
+        }
+    }
+
+    // Appends the minimized form of the node at `root` to `output`.
+    // The offsets of the opening brackets of nodes marked in `inline`
+    // are added to `inline_starts`, leaving out any inside another
+    // marked node.  For objects with a width in `key_widths`, the
+    // offset of each colon is added to `colon_pads` along with the
+    // number of spaces which would line it up with the others.
+    pub fn write_compact(
+        &self,
+        root: usize,
+        output: &mut Vec<u8>,
+        inline: &[bool],
+        key_widths: &[usize],
+        inline_starts: &mut Vec<usize>,
+        colon_pads: &mut Vec<(usize, usize)>,
+    ) {
+        let mut todo = vec![Step::Node(root)];
+        while let Some(step) = todo.pop() {
+            match step {
+                Step::Byte(b) => output.push(b),
+                Step::Pad(pad) => colon_pads.push((output.len(), pad)),
+                Step::Node(index) => match self.nodes[index] {
+                    Node::Scalar(bytes) => output.extend_from_slice(bytes),
+                    _ if inline.get(index) == Some(&true) => {
+                        inline_starts.push(output.len());
+                        self.write_compact(index, output, &[], &[], inline_starts, colon_pads);
+                    }
+                    Node::Array(ref items) => {
+                        output.push(C_LEFT_BRACKET);
+                        todo.push(Step::Byte(C_RIGHT_BRACKET));
+                        for (i, &item) in items.iter().enumerate().rev() {
+                            todo.push(Step::Node(item));
+                            if i > 0 {
+                                todo.push(Step::Byte(C_COMMA));
+                            }
+                        }
+                    }
+                    Node::Object(ref members) => {
+                        output.push(C_LEFT_BRACE);
+                        todo.push(Step::Byte(C_RIGHT_BRACE));
+                        let key_width = key_widths.get(index).cloned().unwrap_or(0);
+                        for (i, member) in members.iter().enumerate().rev() {
+                            if let Some(value) = member.value {
+                                todo.push(Step::Node(value));
+                                todo.push(Step::Byte(C_COLON));
+                                if key_width > 0 {
+                                    todo.push(Step::Pad(key_width - self.width(member.key)));
+                                }
+                            }
+                            todo.push(Step::Node(member.key));
+                            if i > 0 {
+                                todo.push(Step::Byte(C_COMMA));
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+enum Step {
+    Node(usize),
+    Byte(u8),
+    Pad(usize),
+}
+
+// Decodes the escapes in the contents of a string.
+pub fn unescape(bytes: &[u8]) -> Option<String> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    if !s.contains('\\') {
+        return Some(String::from(s));
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut units: Vec<u16> = vec![];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let decoded = match chars.next()? {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let hex: String = chars.by_ref().take(4).collect();
+                units.push(u16::from_str_radix(&hex, 16).ok()?);
+                if (0xD800..0xDC00).contains(&units[0]) && units.len() < 2 {
+                    // Wait for the low surrogate
+                    continue;
+                }
+                let c = std::char::decode_utf16(units.drain(..)).next()?.ok()?;
+                out.push(c);
+                continue;
+            }
+            other => other,
+        };
+        out.push(decoded);
+    }
+    if units.is_empty() {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+// Returns the index just past the string starting at `start`, or
+// None if the input ends first.
+fn string_end(input: &[u8], start: usize) -> Option<usize> {
+    let mut n = start + 1;
+    while n < input.len() {
+        match input[n] {
+            C_QUOTE => return Some(n + 1),
+            C_BACKSLASH => n += 2,
+            _ => n += 1,
+        }
+    }
+    None
+}
@@ -0,0 +1,120 @@
+// Transcoding of UTF-16 input to UTF-8, for `Formatter.decode_utf16`.
+// Input is held here between buffers only as far as needed to finish
+// a byte order mark, a code unit, or a surrogate pair.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Unknown,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+#[derive(Clone, Debug)]
+pub struct Utf16Decoder {
+    encoding: Encoding,
+    held: Vec<u8>,     // bytes of an unfinished BOM or code unit
+    high: Option<u16>, // a high surrogate waiting for its pair
+}
+
+impl Utf16Decoder {
+    pub fn new() -> Utf16Decoder {
+        Utf16Decoder {
+            encoding: Encoding::Unknown,
+            held: vec![],
+            high: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Utf16Decoder::new();
+    }
+
+    // Appends the UTF-8 form of `buf` to `output`.  Input without a
+    // UTF-16 byte order mark is copied as it is.  The byte order mark
+    // itself is written as a UTF-8 one.
+    pub fn decode(&mut self, buf: &[u8], output: &mut Vec<u8>) {
+        let mut buf = buf;
+        if self.encoding == Encoding::Unknown {
+            let need = 2 - self.held.len();
+            self.held.extend_from_slice(&buf[..need.min(buf.len())]);
+            buf = &buf[need.min(buf.len())..];
+            if self.held.len() < 2 {
+                return;
+            }
+            let held = std::mem::take(&mut self.held);
+            self.encoding = match held[..] {
+                [0xFF, 0xFE] => Encoding::Utf16Le,
+                [0xFE, 0xFF] => Encoding::Utf16Be,
+                _ => {
+                    output.extend_from_slice(&held);
+                    Encoding::Utf8
+                }
+            };
+            if self.encoding != Encoding::Utf8 {
+                push_char('\u{feff}', output);
+            }
+        }
+        if self.encoding == Encoding::Utf8 {
+            output.extend_from_slice(buf);
+            return;
+        }
+
+        let mut n = 0;
+        if !self.held.is_empty() && !buf.is_empty() {
+            let pair = [self.held[0], buf[0]];
+            self.held.clear();
+            self.push_unit(pair, output);
+            n = 1;
+        }
+        while n + 1 < buf.len() {
+            self.push_unit([buf[n], buf[n + 1]], output);
+            n += 2;
+        }
+        self.held.extend_from_slice(&buf[n..]);
+    }
+
+    // Writes whatever is left at the end of the input: the start of
+    // something which looked like a byte order mark, or U+FFFD for an
+    // unfinished code unit or surrogate pair.
+    pub fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.encoding == Encoding::Unknown {
+            output.extend_from_slice(&self.held);
+        } else if self.high.is_some() || !self.held.is_empty() {
+            push_char(std::char::REPLACEMENT_CHARACTER, output);
+        }
+        self.reset();
+    }
+
+    fn push_unit(&mut self, bytes: [u8; 2], output: &mut Vec<u8>) {
+        let unit = match self.encoding {
+            Encoding::Utf16Be => u16::from_be_bytes(bytes),
+            _ => u16::from_le_bytes(bytes),
+        };
+        let code = match self.high.take() {
+            Some(high) if (0xDC00..0xE000).contains(&unit) => {
+                0x10000 + ((high as u32 - 0xD800) << 10) + (unit as u32 - 0xDC00)
+            }
+            Some(_) => {
+                push_char(std::char::REPLACEMENT_CHARACTER, output);
+                return self.push_unit(bytes, output);
+            }
+            None if (0xD800..0xDC00).contains(&unit) => {
+                self.high = Some(unit);
+                return;
+            }
+            None => unit as u32,
+        };
+        // Lone low surrogates are not characters
+        let c = std::char::from_u32(code).unwrap_or(std::char::REPLACEMENT_CHARACTER);
+        push_char(c, output);
+    }
+}
+
+fn push_char(c: char, output: &mut Vec<u8>) {
+    let mut utf8 = [0; 4];
+    output.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+}
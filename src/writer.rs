@@ -0,0 +1,86 @@
+use std::io;
+use std::io::prelude::*;
+
+use super::{Formatter, JsonxfError};
+
+/// A `Write` adapter which formats JSON as it is written, passing the
+/// result on to another writer.  Useful with serializers which write
+/// to a `Write`, such as `serde_json::to_writer`.
+///
+/// The formatting is finished when the `PrettyWriter` is dropped, or
+/// by calling [`PrettyWriter::finish`] to see any error.
+///
+/// # Example:
+///
+/// ```
+/// use std::io::Write;
+/// let mut output: Vec<u8> = vec![];
+/// {
+///     let mut writer = jsonxf::PrettyWriter::new(&mut output, "\t");
+///     writer.write_all(b"{\"a\":").unwrap();
+///     writer.write_all(b"[1,2]}").unwrap();
+/// }
+/// assert_eq!(output, b"{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t]\n}");
+/// ```
+pub struct PrettyWriter<W: Write> {
+    inner: Option<W>,
+    formatter: Formatter,
+}
+
+impl<W: Write> PrettyWriter<W> {
+    /// Returns a `PrettyWriter` which pretty-prints to `inner`, using
+    /// `indent` for indentation.
+    pub fn new(inner: W, indent: &str) -> PrettyWriter<W> {
+        let mut formatter = Formatter::pretty_printer();
+        formatter.indent = String::from(indent);
+        PrettyWriter::with_formatter(inner, formatter)
+    }
+
+    /// Returns a `PrettyWriter` which formats with `formatter`, such as
+    /// a `Formatter::minimizer()`.
+    pub fn with_formatter(inner: W, formatter: Formatter) -> PrettyWriter<W> {
+        PrettyWriter {
+            inner: Some(inner),
+            formatter,
+        }
+    }
+
+    /// Returns a reference to the writer formatted output goes to.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Finishes formatting, as with [`Formatter::finish`], and returns
+    /// the underlying writer.
+    pub fn finish(mut self) -> Result<W, JsonxfError> {
+        let mut inner = self.inner.take().unwrap();
+        self.formatter.finish(&mut inner)?;
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for PrettyWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = self.inner.as_mut().unwrap();
+        match self.formatter.format_buf(buf, inner) {
+            Ok(()) => Ok(buf.len()),
+            Err(JsonxfError::Io(e)) => Err(e),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for PrettyWriter<W> {
+    fn drop(&mut self) {
+        if let Some(mut inner) = self.inner.take() {
+            // Errors can't be reported from here; see finish()
+            let _ = self.formatter.finish(&mut inner);
+            let _ = inner.flush();
+        }
+    }
+}
@@ -0,0 +1,69 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn align(input: &str) -> String {
+    let mut xf = Formatter::pretty_printer();
+    xf.align_values = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn values_line_up_within_an_object() {
+    assert_eq!(
+        align("{\"a\":1,\"long\":2,\"mid\":3}"),
+        "{\n  \"a\"   : 1,\n  \"long\": 2,\n  \"mid\" : 3\n}"
+    );
+}
+
+#[test]
+fn each_object_is_aligned_separately() {
+    assert_eq!(
+        align("{\"outer\":{\"x\":1,\"yy\":2},\"o\":[{\"abc\":1,\"d\":2}]}"),
+        "{\n  \"outer\": {\n    \"x\" : 1,\n    \"yy\": 2\n  },\n  \"o\"    : [\n    {\n      \"abc\": 1,\n      \"d\"  : 2\n    }\n  ]\n}"
+    );
+}
+
+#[test]
+fn counts_characters_not_bytes() {
+    assert_eq!(
+        align("{\"é\":1,\"ab\":2}"),
+        "{\n  \"é\" : 1,\n  \"ab\": 2\n}"
+    );
+}
+
+#[test]
+fn one_line_objects_are_not_aligned() {
+    let mut xf = Formatter::pretty_printer();
+    xf.align_values = true;
+    xf.max_inline_length = Some(20);
+    assert_eq!(
+        xf.format("{\"a\":{\"b\":1,\"ccc\":2},\"dddddd\":[1,2,3,4,5,6,7,8,9]}")
+            .unwrap(),
+        "{\n  \"a\"     : {\"b\": 1, \"ccc\": 2},\n  \"dddddd\": [\n    1,\n    2,\n    3,\n    4,\n    5,\n    6,\n    7,\n    8,\n    9\n  ]\n}"
+    );
+}
+
+#[test]
+fn minimized_output_ignores_it() {
+    let mut xf = Formatter::minimizer();
+    xf.align_values = true;
+    assert_eq!(
+        xf.format("{ \"a\": 1, \"long\": 2 }").unwrap(),
+        "{\"a\":1,\"long\":2}"
+    );
+}
+
+#[test]
+fn works_across_buffers_and_records() {
+    let mut xf = Formatter::pretty_printer();
+    xf.align_values = true;
+    let mut output: Vec<u8> = vec![];
+    xf.format_buf(b"{\"a\":1,\"bb\"", &mut output).unwrap();
+    xf.format_buf(b":2} {\"ccc\":3,\"d\":4}", &mut output)
+        .unwrap();
+    xf.finish(&mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\n  \"a\" : 1,\n  \"bb\": 2\n}\n{\n  \"ccc\": 3,\n  \"d\"  : 4\n}"
+    );
+}
@@ -0,0 +1,86 @@
+extern crate jsonxf;
+use jsonxf::{FormattedChunks, Formatter, JsonxfError};
+use std::io::{self, Read};
+
+// Hands out its input a few bytes at a time.
+struct SlowReader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Read for SlowReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.len().min(buf.len()).min(3);
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn yields_output_as_input_is_read() {
+    let input = SlowReader {
+        input: b"[1, 2, 3]",
+    };
+    let chunks: Vec<Vec<u8>> = FormattedChunks::new(input, Formatter::minimizer())
+        .map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks.concat(), b"[1,2,3]");
+}
+
+#[test]
+fn input_is_read_in_buffer_capacity_chunks() {
+    let mut xf = Formatter::minimizer();
+    xf.buffer_capacity = 2;
+    let input: &[u8] = b"[1, 2]";
+    let chunks: Vec<Vec<u8>> = FormattedChunks::new(input, xf)
+        .map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(chunks, vec![b"[1".to_vec(), b",".to_vec(), b"2]".to_vec()]);
+}
+
+#[test]
+fn trailing_output_comes_last() {
+    let mut xf = Formatter::pretty_printer();
+    xf.trailing_output = String::from("\n");
+    let input: &[u8] = b"{\"a\":1}";
+    let chunks: Vec<Vec<u8>> = FormattedChunks::new(input, xf)
+        .map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(chunks, vec![b"{\n  \"a\": 1\n}".to_vec(), b"\n".to_vec()]);
+}
+
+#[test]
+fn buffered_options_yield_one_chunk() {
+    let mut xf = Formatter::minimizer();
+    xf.sort_keys = true;
+    let input = SlowReader {
+        input: b"{\"b\": 1, \"a\": 2}",
+    };
+    let chunks: Vec<Vec<u8>> = FormattedChunks::new(input, xf)
+        .map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(chunks, vec![b"{\"a\":2,\"b\":1}".to_vec()]);
+}
+
+#[test]
+fn empty_input_yields_nothing() {
+    let input: &[u8] = b"";
+    assert_eq!(
+        FormattedChunks::new(input, Formatter::pretty_printer()).count(),
+        0
+    );
+}
+
+#[test]
+fn ends_after_an_error() {
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    let input: &[u8] = b"[1]]";
+    let mut chunks = FormattedChunks::new(input, xf);
+    match chunks.next() {
+        Some(Err(JsonxfError::UnexpectedClose { offset: 3 })) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(chunks.next().is_none());
+}
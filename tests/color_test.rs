@@ -0,0 +1,70 @@
+extern crate jsonxf;
+use jsonxf::{ColorScheme, Formatter};
+
+fn scheme() -> ColorScheme {
+    ColorScheme {
+        key: String::from("<k>"),
+        string: String::from("<s>"),
+        number: String::from("<n>"),
+        literal: String::from("<l>"),
+        punctuation: String::from("<p>"),
+    }
+}
+
+fn colorize(mut xf: Formatter, input: &str) -> String {
+    xf.colors = Some(scheme());
+    xf.format(input).unwrap().replace("\x1b[0m", "|")
+}
+
+#[test]
+fn colors_each_kind_of_token() {
+    assert_eq!(
+        colorize(
+            Formatter::minimizer(),
+            "{\"a\": [1, \"x\", true, null], \"b\": {\"c\": -2.5e3}}"
+        ),
+        "<p>{|<k>\"a\"|<p>:|<p>[|<n>1|<p>,|<s>\"x\"|<p>,|<l>true|<p>,|<l>null|<p>]|<p>,|\
+         <k>\"b\"|<p>:|<p>{|<k>\"c\"|<p>:|<n>-2.5e3|<p>}|<p>}|"
+    );
+}
+
+#[test]
+fn colors_go_around_whitespace_and_escapes() {
+    assert_eq!(
+        colorize(
+            Formatter::pretty_printer(),
+            "{\"k\\\"é\":[\"\\u00e9\\\\\"]}"
+        ),
+        "<p>{|\n  <k>\"k\\\"é\"|<p>:| <p>[|\n    <s>\"\\u00e9\\\\\"|\n  <p>]|\n<p>}|"
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.ascii_only = true;
+    assert_eq!(colorize(xf, "[\"é\"]"), "<p>[|<s>\"\\u00e9\"|<p>]|");
+}
+
+#[test]
+fn colors_top_level_values() {
+    let mut xf = Formatter::minimizer();
+    xf.eager_record_separators = true;
+    assert_eq!(colorize(xf, "1 \"a\" []"), "<n>1|\n<s>\"a\"|\n<p>[|<p>]|\n");
+    assert_eq!(colorize(Formatter::minimizer(), "\"open"), "<s>\"open|");
+}
+
+#[test]
+fn colors_with_sort_keys() {
+    let mut xf = Formatter::minimizer();
+    xf.sort_keys = true;
+    assert_eq!(
+        colorize(xf, "{\"b\":1,\"a\":2}"),
+        "<p>{|<k>\"a\"|<p>:|<n>2|<p>,|<k>\"b\"|<p>:|<n>1|<p>}|"
+    );
+}
+
+#[test]
+fn no_colors_by_default() {
+    assert_eq!(
+        Formatter::pretty_printer().format("{\"a\":[1]}").unwrap(),
+        "{\n  \"a\": [\n    1\n  ]\n}"
+    );
+}
@@ -0,0 +1,157 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn strip(input: &str) -> String {
+    let mut xf = Formatter::minimizer();
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn strips_line_and_block_comments() {
+    assert_eq!(
+        strip("// header\n{\n  \"a\": 1, // one\n  /* two */ \"b\": 2\n}\n"),
+        "{\"a\":1,\"b\":2}"
+    );
+    assert_eq!(strip("[1 /* a\n * b\n **/, 2]"), "[1,2]");
+}
+
+#[test]
+fn keeps_comment_markers_in_strings() {
+    assert_eq!(
+        strip("{\"url\": \"http://example.com/*x*/\"}"),
+        "{\"url\":\"http://example.com/*x*/\"}"
+    );
+}
+
+#[test]
+fn comments_after_colons_and_commas() {
+    assert_eq!(
+        strip("{\"a\"://x\n1,/*y*/\"b\":/**/2}"),
+        "{\"a\":1,\"b\":2}"
+    );
+}
+
+#[test]
+fn lone_slash_is_kept() {
+    assert_eq!(strip("[1/2, 3]"), "[1/2,3]");
+    assert_eq!(strip("[1]/"), "[1]\n/");
+}
+
+#[test]
+fn unterminated_block_comment() {
+    assert_eq!(strip("[1] /* never ends"), "[1]");
+}
+
+#[test]
+fn comments_are_kept_by_default() {
+    assert_eq!(jsonxf::minimize("[1 /*x*/]").unwrap(), "[1/*x*/]");
+}
+
+#[test]
+fn pretty_print_strips_comments() {
+    let mut xf = Formatter::pretty_printer();
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    assert_eq!(
+        xf.format("{\"a\": [1, // first\n 2]}").unwrap(),
+        "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+    );
+}
+
+fn preserve(input: &str) -> String {
+    let mut xf = Formatter::pretty_printer();
+    xf.comment_mode = jsonxf::CommentMode::Preserve;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn preserves_comments_when_pretty_printing() {
+    assert_eq!(
+        preserve("// header\n{\"a\": 1, // one\n  /* two */ \"b\": 2\n}\n"),
+        "// header\n{\n  \"a\": 1, // one\n  /* two */ \"b\": 2\n}"
+    );
+}
+
+#[test]
+fn preserves_comments_on_their_own_lines() {
+    assert_eq!(
+        preserve("{\n\"a\": 1,\n    // c\n\"b\": [\n// d\n2]}"),
+        "{\n  \"a\": 1,\n  // c\n  \"b\": [\n    // d\n    2\n  ]\n}"
+    );
+    assert_eq!(preserve("[1]\n// c\n[2]"), "[\n  1\n]\n// c\n[\n  2\n]");
+}
+
+#[test]
+fn preserves_comments_between_keys_and_values() {
+    assert_eq!(
+        preserve("{\"a\": // k\n 1, \"b\": /* v */ 2}"),
+        "{\n  \"a\": // k\n  1,\n  \"b\": /* v */ 2\n}"
+    );
+}
+
+#[test]
+fn preserves_comments_before_closing_brackets() {
+    assert_eq!(preserve("[1, 2 // last\n]"), "[\n  1,\n  2 // last\n]");
+    assert_eq!(preserve("[1, 2\n// last\n]"), "[\n  1,\n  2\n  // last\n]");
+    assert_eq!(preserve("{\n// only\n}"), "{\n  // only\n}");
+}
+
+#[test]
+fn preserved_line_comments_end_lines_in_minimized_output() {
+    let mut xf = Formatter::minimizer();
+    xf.comment_mode = jsonxf::CommentMode::Preserve;
+    assert_eq!(
+        xf.format("[1, // one\r\n 2 /* two */]").unwrap(),
+        "[1, // one\n2 /* two */]"
+    );
+}
+
+#[test]
+fn preserved_comments_and_trailing_commas() {
+    let mut xf = Formatter::pretty_printer();
+    xf.comment_mode = jsonxf::CommentMode::Preserve;
+    xf.remove_trailing_commas = true;
+    assert_eq!(
+        xf.format("[1, // one\n 2, // two\n]").unwrap(),
+        "[\n  1, // one\n  2 // two\n]"
+    );
+}
+
+#[test]
+fn preserved_comments_across_buffers() {
+    let mut xf = Formatter::pretty_printer();
+    xf.comment_mode = jsonxf::CommentMode::Preserve;
+    let mut output: Vec<u8> = vec![];
+    for chunk in ["[1, /", "/ on", "e\n 2 /", "* two *", "/]"].iter() {
+        xf.format_buf(chunk.as_bytes(), &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"[\n  1, // one\n  2 /* two */\n]");
+}
+
+#[test]
+fn reject_comments() {
+    let mut xf = Formatter::minimizer();
+    xf.comment_mode = jsonxf::CommentMode::Reject;
+    for &(input, offset) in [("[1, // one\n 2]", 4), ("/**/1", 0), ("{\"a\":1/*", 6)].iter() {
+        match xf.format(input) {
+            Err(jsonxf::JsonxfError::Comment { offset: found }) => assert_eq!(offset, found),
+            other => panic!("{}: {:?}", input, other),
+        }
+    }
+    assert_eq!(xf.format("[\"// /* */\", 1]").unwrap(), "[\"// /* */\",1]");
+
+    let mut output: Vec<u8> = vec![];
+    xf.reset();
+    xf.format_buf(b"[1, /", &mut output).unwrap();
+    assert!(matches!(
+        xf.format_buf(b"/ one\n]", &mut output),
+        Err(jsonxf::JsonxfError::Comment { offset: 4 })
+    ));
+
+    xf.sort_keys = true;
+    assert!(matches!(
+        xf.format("{\"b\": 1, /* a */ \"a\": 2}"),
+        Err(jsonxf::JsonxfError::Comment { offset: 9 })
+    ));
+}
@@ -0,0 +1,119 @@
+extern crate jsonxf;
+use jsonxf::{DuplicateKeys, Formatter};
+
+fn dedup(mut xf: Formatter, policy: DuplicateKeys, input: &str) -> String {
+    xf.duplicate_keys = policy;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn keeps_duplicates_by_default() {
+    let input = "{\"a\":1,\"a\":2}";
+    assert_eq!(Formatter::minimizer().format(input).unwrap(), input);
+    assert_eq!(
+        dedup(Formatter::minimizer(), DuplicateKeys::Keep, input),
+        input
+    );
+}
+
+#[test]
+fn last_wins_at_its_own_position() {
+    assert_eq!(
+        dedup(
+            Formatter::minimizer(),
+            DuplicateKeys::Last,
+            "{\"a\": 1, \"b\": 2, \"a\": 3, \"c\": 4}"
+        ),
+        "{\"b\":2,\"a\":3,\"c\":4}"
+    );
+}
+
+#[test]
+fn first_wins_at_its_own_position() {
+    assert_eq!(
+        dedup(
+            Formatter::minimizer(),
+            DuplicateKeys::First,
+            "{\"a\": 1, \"b\": 2, \"a\": 3, \"c\": 4}"
+        ),
+        "{\"a\":1,\"b\":2,\"c\":4}"
+    );
+}
+
+#[test]
+fn dedups_nested_objects_separately() {
+    assert_eq!(
+        dedup(
+            Formatter::minimizer(),
+            DuplicateKeys::Last,
+            "[{\"a\": {\"a\": 1, \"a\": 2}, \"a\": {\"b\": 3}}, {\"a\": 4}]"
+        ),
+        "[{\"a\":{\"b\":3}},{\"a\":4}]"
+    );
+}
+
+#[test]
+fn compares_decoded_keys() {
+    assert_eq!(
+        dedup(
+            Formatter::minimizer(),
+            DuplicateKeys::Last,
+            "{\"\\u0061\": 1, \"a\": 2, \"\\\"\": 3, \"\\u0022\": 4}"
+        ),
+        "{\"a\":2,\"\\u0022\":4}"
+    );
+}
+
+#[test]
+fn works_with_sort_keys_and_pretty_printing() {
+    let mut xf = Formatter::pretty_printer();
+    xf.sort_keys = true;
+    assert_eq!(
+        dedup(xf, DuplicateKeys::Last, "{\"b\": 1, \"a\": 2, \"b\": 3}"),
+        "{\n  \"a\": 2,\n  \"b\": 3\n}"
+    );
+}
+
+#[test]
+fn works_with_streams() {
+    let mut xf = Formatter::minimizer();
+    xf.duplicate_keys = DuplicateKeys::First;
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream(
+        &mut &b"{\"a\": 1, \"a\": 2} {\"b\": 3, \"b\": 4}"[..],
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "{\"a\":1}\n{\"b\":3}");
+}
+
+fn found(input: &str) -> Vec<(String, String)> {
+    Formatter::pretty_printer()
+        .find_duplicate_keys(input)
+        .unwrap()
+}
+
+#[test]
+fn finds_duplicates_with_their_paths() {
+    let pair = |path: &str, key: &str| (String::from(path), String::from(key));
+    assert_eq!(found("{\"a\": 1, \"b\": 2}"), vec![]);
+    assert_eq!(
+        found("{\"a\": 1, \"a\": 2, \"a\": 3, \"\\u0062\": 4, \"b\": 5}"),
+        vec![pair("", "a"), pair("", "b")]
+    );
+    assert_eq!(
+        found("[{\"x/y\": {\"~\": [{\"c\": 1, \"c\": 2}]}}] {\"d\": 1, \"d\": 1}"),
+        vec![pair("/0/x~1y/~0/0", "c"), pair("", "d")]
+    );
+}
+
+#[test]
+fn finding_duplicates_leaves_formatting_alone() {
+    let input = "{\"a\": 1, \"a\": 2}";
+    let mut xf = Formatter::minimizer();
+    assert_eq!(xf.find_duplicate_keys(input).unwrap().len(), 1);
+    assert_eq!(xf.format(input).unwrap(), "{\"a\":1,\"a\":2}");
+    xf.duplicate_keys = DuplicateKeys::Last;
+    assert_eq!(xf.find_duplicate_keys(input).unwrap().len(), 1);
+    assert_eq!(xf.format(input).unwrap(), "{\"a\":2}");
+}
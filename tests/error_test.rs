@@ -0,0 +1,60 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, JsonxfError};
+use std::io::{self, Read, Write};
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    #[allow(clippy::io_other_error)] // io::Error::other needs Rust 1.74
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "no input for you"))
+    }
+}
+
+#[test]
+fn io_errors() {
+    let mut output: Vec<u8> = vec![];
+    match jsonxf::pretty_print_stream(&mut FailingReader, &mut output) {
+        Err(JsonxfError::Io(ref e)) if e.kind() == io::ErrorKind::Other => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut xf = Formatter::minimizer();
+    let err = xf
+        .format_stream(&mut FailingReader, &mut output)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no input for you");
+}
+
+// Accepts writes, but fails to pass them on when flushed, as a full
+// disk or a closed pipe might.
+struct FailingFlush {
+    written: usize,
+}
+
+impl Write for FailingFlush {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "no room"))
+    }
+}
+
+#[test]
+fn flush_errors_are_reported() {
+    let mut output = FailingFlush { written: 0 };
+    match jsonxf::minimize_stream(&mut &b"[1, 2]"[..], &mut output) {
+        Err(JsonxfError::Io(ref e)) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(output.written, 5);
+
+    let mut output = FailingFlush { written: 0 };
+    let err = Formatter::pretty_printer()
+        .format_ndjson_stream(&mut &b"1\n2\n"[..], &mut output)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no room");
+}
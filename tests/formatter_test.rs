@@ -11,6 +11,48 @@ fn indent() {
     );
 }
 
+#[test]
+fn input_indentation_is_normalized() {
+    // Tabs and spaces mixed inconsistently, and at the wrong depths
+    let input =
+        "{\n\t\"a\": [\n  \t    1,\n\t\t\t\t{ \"b\":\n\n 2 }\r\n ],\n        \"c\": {}\n\t}";
+    assert_eq!(
+        Formatter::pretty_printer()
+            .with_tabs()
+            .format(input)
+            .unwrap(),
+        "{\n\t\"a\": [\n\t\t1,\n\t\t{\n\t\t\t\"b\": 2\n\t\t}\n\t],\n\t\"c\": {}\n}"
+    );
+    assert_eq!(
+        Formatter::pretty_printer()
+            .with_spaces(3)
+            .format(input)
+            .unwrap(),
+        "{\n   \"a\": [\n      1,\n      {\n         \"b\": 2\n      }\n   ],\n   \"c\": {}\n}"
+    );
+    assert_eq!(
+        Formatter::pretty_printer()
+            .with_spaces(0)
+            .format(input)
+            .unwrap(),
+        "{\n\"a\": [\n1,\n{\n\"b\": 2\n}\n],\n\"c\": {}\n}"
+    );
+    assert_eq!(
+        Formatter::pretty_printer()
+            .indent_tabs(2)
+            .format("{\"a\": [1]}")
+            .unwrap(),
+        "{\n\t\t\"a\": [\n\t\t\t\t1\n\t\t]\n}"
+    );
+    assert_eq!(
+        Formatter::pretty_printer()
+            .indent_spaces(4)
+            .format("[1]")
+            .unwrap(),
+        "[\n    1\n]"
+    );
+}
+
 #[test]
 fn line_separator() {
     let mut xf = Formatter::minimizer();
@@ -51,3 +93,484 @@ fn trailing_output() {
         xf.format("{\"a\":{\"b\":{\"c\":3}}}").unwrap()
     );
 }
+
+#[test]
+fn default_formatters_match_free_functions() {
+    let input = " { \"a\": [1, 2, {}], \"b\": { \"c\": null } } [ ] ";
+    assert_eq!(
+        jsonxf::pretty_print(input).unwrap(),
+        Formatter::pretty_printer().format(input).unwrap()
+    );
+    assert_eq!(
+        jsonxf::minimize(input).unwrap(),
+        Formatter::minimizer().format(input).unwrap()
+    );
+}
+
+#[test]
+fn format_stream() {
+    let mut xf = Formatter::pretty_printer();
+    xf.indent = String::from("\t");
+    xf.after_colon = String::from("");
+    let mut input = "{\"a\":[1]}".as_bytes();
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream(&mut input, &mut output).unwrap();
+    assert_eq!(output, b"{\n\t\"a\":[\n\t\t1\n\t]\n}");
+}
+
+#[test]
+fn before_colon() {
+    let mut xf = Formatter::pretty_printer();
+    xf.before_colon = String::from(" ");
+    assert_eq!(
+        "{\n  \"a:b\" : \"c:d\"\n}",
+        xf.format("{\"a:b\":\"c:d\"}").unwrap()
+    );
+}
+
+#[test]
+fn crlf_line_endings() {
+    let mut xf = Formatter::pretty_printer();
+    xf.line_separator = String::from("\r\n");
+    xf.record_separator = String::from("\r\n");
+    xf.eager_record_separators = true;
+    assert_eq!(
+        "{\r\n  \"a\": [\r\n    1\r\n  ]\r\n}\r\n[]\r\n",
+        xf.format("{\"a\":[1]}\n[]\n").unwrap()
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.record_separator = String::from("\r\n");
+    assert_eq!(
+        "{\"a\":[1]}\r\n[]",
+        xf.format("{\r\n  \"a\": [\r\n    1\r\n  ]\r\n}\r\n[]\r\n")
+            .unwrap()
+    );
+
+    let mut xf = Formatter::minimizer().with_line_ending("\r\n");
+    assert_eq!("{\"a\":[1]}\r\n[]", xf.format("{\"a\": [1]} []").unwrap());
+
+    let mut xf = Formatter::pretty_printer().with_line_ending("\r\n");
+    xf.trailing_output = String::from("\n");
+    xf = xf.with_line_ending("\r\n");
+    assert_eq!(
+        "{\r\n  \"a\": [\r\n    1\r\n  ]\r\n}\r\n[]\r\n",
+        xf.format("{\"a\":[1]}\n[]\n").unwrap()
+    );
+}
+
+#[test]
+fn remove_trailing_commas() {
+    let test_cases = vec![
+        ("[1,2,]", "[1,2]"),
+        ("{\"a\":1 ,\n}", "{\"a\":1}"),
+        ("[1,,]", "[1]"),
+        ("[1,,2]", "[1,2]"),
+        ("[\"a,]\",]", "[\"a,]\"]"),
+        ("[[1,],{},]", "[[1],{}]"),
+    ];
+    for (input, output) in test_cases {
+        let mut xf = Formatter::minimizer();
+        xf.remove_trailing_commas = true;
+        assert_eq!(output, xf.format(input).unwrap());
+    }
+
+    let mut xf = Formatter::pretty_printer();
+    xf.remove_trailing_commas = true;
+    assert_eq!("[\n  1,\n  2\n]", xf.format("[1, 2, ]").unwrap());
+
+    let mut xf = Formatter::minimizer();
+    xf.remove_trailing_commas = true;
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    assert_eq!("[1]", xf.format("[1, // last\n]").unwrap());
+}
+
+#[test]
+fn byte_order_mark() {
+    assert_eq!("{}", jsonxf::minimize("\u{feff}{}").unwrap());
+    assert_eq!("{}", jsonxf::pretty_print("\u{feff}{}").unwrap());
+    assert_eq!("[\n  1\n]", jsonxf::pretty_print("\u{feff} [1]").unwrap());
+
+    let mut xf = Formatter::minimizer();
+    xf.preserve_bom = true;
+    assert_eq!("\u{feff}[1]", xf.format("\u{feff}[ 1 ]").unwrap());
+
+    let mut xf = Formatter::minimizer();
+    xf.sort_keys = true;
+    assert_eq!(
+        "{\"a\":1,\"b\":2}",
+        xf.format("\u{feff}{\"b\":2,\"a\":1}").unwrap()
+    );
+
+    // A BOM split across buffers, and a near miss
+    for input in [&b"\xef\xbb\xbf[1]"[..], &b"\xef\xbb[1]"[..], &b"\xef"[..]] {
+        let mut xf = Formatter::minimizer();
+        let mut output: Vec<u8> = vec![];
+        for chunk in input.chunks(1) {
+            xf.format_buf(chunk, &mut output).unwrap();
+        }
+        xf.finish(&mut output).unwrap();
+        let expected: &[u8] = match input.len() {
+            6 => &input[3..],
+            5 => b"\xef\xbb\n[1]",
+            _ => input,
+        };
+        assert_eq!(expected, &output[..]);
+    }
+}
+
+#[test]
+fn record_separator_between_scalars() {
+    let test_cases = vec![
+        ("1 2", "1\n2"),
+        ("1 [2]", "1\n[2]"),
+        ("[1] 2", "[1]\n2"),
+        ("\"a\"\"b\"", "\"a\"\n\"b\""),
+        ("true{}", "true\n{}"),
+    ];
+    for (input, output) in test_cases {
+        assert_eq!(output, jsonxf::minimize(input).unwrap());
+    }
+
+    let mut xf = Formatter::minimizer();
+    xf.record_separator = String::from(" ");
+    assert_eq!("1 2 {} 3", xf.format("1\n2{}3").unwrap());
+
+    let mut xf = Formatter::minimizer();
+    xf.record_separator = String::from("");
+    assert_eq!("[1]{}\"a\"", xf.format("[1]\n{}\n\"a\"").unwrap());
+}
+
+#[test]
+fn reuse_formatter() {
+    let mut xf = Formatter::pretty_printer();
+    xf.sort_keys = true;
+    for _ in 0..3 {
+        assert_eq!(
+            "{\n  \"a\": 1,\n  \"b\": [\n    2\n  ]\n}",
+            xf.format("{\"b\":[2],\"a\":1}").unwrap()
+        );
+    }
+
+    // State left by unfinished input is cleared too
+    let mut xf = Formatter::minimizer();
+    let mut output: Vec<u8> = vec![];
+    xf.format_buf(b"[\"abc", &mut output).unwrap();
+    assert_eq!("[1]", xf.format("[ 1 ]").unwrap());
+}
+
+#[test]
+fn empty_style() {
+    use jsonxf::EmptyStyle;
+
+    let input = "{\"a\":{},\"b\":[[]]}";
+    let test_cases = vec![
+        (
+            EmptyStyle::Compact,
+            "{\n  \"a\": {},\n  \"b\": [\n    []\n  ]\n}",
+        ),
+        (
+            EmptyStyle::Spaced,
+            "{\n  \"a\": { },\n  \"b\": [\n    [ ]\n  ]\n}",
+        ),
+        (
+            EmptyStyle::Expanded,
+            "{\n  \"a\": {\n  },\n  \"b\": [\n    [\n    ]\n  ]\n}",
+        ),
+    ];
+    for (style, output) in test_cases {
+        let mut xf = Formatter::pretty_printer();
+        xf.empty_style = style;
+        assert_eq!(output, xf.format(input).unwrap());
+    }
+
+    let mut xf = Formatter::pretty_printer();
+    xf.empty_style = EmptyStyle::Expanded;
+    assert_eq!("[\n]\n{\n}", xf.format("[] {}").unwrap());
+}
+
+#[test]
+fn bracket_padding_and_after_comma() {
+    let mut xf = Formatter::single_line();
+    assert_eq!(
+        "{ \"a\": 1, \"b\": [ [ 2 ], [], { \"c\": null } ] }\n[ 3 ]",
+        xf.format("{\"a\":1,\"b\":[[2],[],{\"c\":null}]}\n[3]")
+            .unwrap()
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.after_comma = String::from(" ");
+    assert_eq!(
+        "[1, 2, {\"a\":3, \"b\":4}]",
+        xf.format("[1,2,{\"a\":3,\"b\":4}]").unwrap()
+    );
+
+    let mut xf = Formatter::single_line();
+    xf.sort_keys = true;
+    xf.empty_style = jsonxf::EmptyStyle::Spaced;
+    assert_eq!(
+        "{ \"a\": { }, \"b\": 1 }",
+        xf.format("{\"b\":1,\"a\":{}}").unwrap()
+    );
+}
+
+#[test]
+fn padding_per_bracket() {
+    let mut xf = Formatter::minimizer();
+    xf.bracket_padding = String::from("_");
+    xf.after_array_open = Some(String::from("<"));
+    xf.before_array_close = Some(String::from(">"));
+    xf.after_object_open = Some(String::from("("));
+    xf.before_object_close = Some(String::from(")"));
+    assert_eq!(
+        "[<{(\"a\":[<1>],\"b\":{})},[]>]",
+        xf.format("[{\"a\":[1],\"b\":{}},[]]").unwrap()
+    );
+
+    xf.before_array_close = None;
+    xf.sort_keys = true;
+    assert_eq!("{(\"a\":[<1_])}", xf.format("{\"a\":[1]}").unwrap());
+
+    let mut xf = Formatter::pretty_printer();
+    xf.after_object_open = Some(String::from(" "));
+    assert_eq!("{\n  \"a\": []\n}", xf.format("{\"a\":[]}").unwrap());
+
+    let mut xf = Formatter::pretty_printer();
+    xf.bracket_padding = String::from(" ");
+    xf.max_inline_length = Some(20);
+    assert_eq!(
+        "{\n  \"a\": [ 1, 2 ],\n  \"b\": [\n    \"a string too long to fit\"\n  ]\n}",
+        xf.format("{\"a\":[1,2],\"b\":[\"a string too long to fit\"]}")
+            .unwrap()
+    );
+}
+
+#[test]
+fn spaced_minimizer() {
+    let mut xf = Formatter::spaced_minimizer();
+    assert_eq!(
+        "{\"a\": \"b\", \"c\": [0, {}, \"x, y: z\"]}\n[]",
+        xf.format("{\n  \"a\" : \"b\",\n  \"c\": [ 0, { }, \"x, y: z\" ]\n}\n[ ]")
+            .unwrap()
+    );
+}
+
+#[test]
+fn depth_indents() {
+    let mut xf = Formatter::pretty_printer();
+    xf.indent = String::from("    ");
+    xf.depth_indents = vec![String::from(""), String::from("  ")];
+    assert_eq!(
+        "{\n  \"a\": [\n      1,\n      [\n          2\n      ]\n  ]\n}",
+        xf.format("{\"a\":[1,[2]]}").unwrap()
+    );
+
+    let mut xf = Formatter::pretty_printer();
+    xf.depth_indents = vec![String::from(">"), String::from(">-"), String::from(">--")];
+    assert_eq!(">[\n>-[\n>--1\n>-]\n>]", xf.format("[[1]]").unwrap());
+    assert_eq!(">[\n>-1\n>]\n>2", xf.format("[1] 2").unwrap());
+}
+
+#[test]
+fn array_and_object_indents() {
+    let mut xf = Formatter::minimizer();
+    xf.indent = String::from("X");
+    xf.array_indent = Some(String::from("A"));
+    xf.object_indent = Some(String::from("O"));
+    assert_eq!(
+        "{O\"a\":[OA{OAO\"b\":[]OA},OA2O]}",
+        xf.format("{\"a\":[{\"b\":[]},2]}").unwrap()
+    );
+
+    xf.object_indent = None;
+    assert_eq!("{X\"a\":[XA1X]}", xf.format("{\"a\":[1]}").unwrap());
+
+    xf.depth_indents = vec![String::from(""), String::from("-")];
+    assert_eq!("[-[-A1-]]", xf.format("[[1]]").unwrap());
+
+    xf.sort_keys = true;
+    assert_eq!(
+        "{-\"a\":[-A1-],-\"b\":2}",
+        xf.format("{\"b\":2,\"a\":[1]}").unwrap()
+    );
+}
+
+#[test]
+fn default_and_debug() {
+    let mut xf = Formatter::default();
+    assert_eq!(
+        format!("{:?}", xf),
+        format!("{:?}", Formatter::pretty_printer())
+    );
+    assert_eq!(xf.format("[1]").unwrap(), "[\n  1\n]");
+
+    // Settings are shown, but not the state of formatting under way
+    let mut output: Vec<u8> = vec![];
+    xf.format_buf(b"[[", &mut output).unwrap();
+    let debug = format!("{:?}", xf);
+    assert_eq!(debug, format!("{:?}", Formatter::default()));
+    assert!(debug.starts_with("Formatter { indent: \"  \", depth_indents: [], "));
+    assert!(debug.contains("empty_style: Compact, "));
+}
+
+#[test]
+fn debug_shows_every_setting() {
+    let source = include_str!("../src/jsonxf.rs");
+    let start = source.find("pub struct Formatter {").unwrap();
+    let end = start + source[start..].find("\n}").unwrap();
+    let debug = format!("{:?}", Formatter::default());
+    for line in source[start..end].lines() {
+        if let Some(field) = line.strip_prefix("    pub ") {
+            let name = &field[..field.find(':').unwrap()];
+            assert!(
+                debug.contains(&format!(" {}: ", name)),
+                "{} is missing from Debug",
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn base_indent_level() {
+    let mut xf = Formatter::pretty_printer();
+    xf.indent = String::from("-");
+    xf.base_indent_level = 2;
+    assert_eq!(
+        xf.format("{\"a\": [1, {}]} [] \"x\"").unwrap(),
+        "--{\n---\"a\": [\n----1,\n----{}\n---]\n--}\n--[]\n--\"x\""
+    );
+
+    xf.eager_record_separators = true;
+    xf.sort_keys = true;
+    assert_eq!(
+        xf.format("{\"b\": 1, \"a\": 2}").unwrap(),
+        "--{\n---\"a\": 2,\n---\"b\": 1\n--}\n"
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.base_indent_level = 3;
+    assert_eq!(xf.format("[1, [2]]").unwrap(), "[1,[2]]");
+}
+
+#[test]
+fn max_indent_level() {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_indent_level = Some(2);
+    assert_eq!(
+        "{\n  \"a\": [\n    [\n    [\n    1\n    ],\n    2\n    ]\n  ]\n}",
+        xf.format("{\"a\":[[[1],2]]}").unwrap()
+    );
+
+    xf.base_indent_level = 1;
+    assert_eq!("  [\n    [\n    1\n    ]\n  ]", xf.format("[[1]]").unwrap());
+
+    let mut xf = Formatter::pretty_printer();
+    xf.max_indent_level = Some(1);
+    xf.array_indent = Some(String::from("A"));
+    xf.object_indent = Some(String::from("O"));
+    xf.indent = String::from("X");
+    assert_eq!("{\nO\"a\": [\nO1\nO]\n}", xf.format("{\"a\":[1]}").unwrap());
+
+    let mut xf = Formatter::pretty_printer();
+    xf.max_indent_level = Some(1);
+    xf.wrap_width = Some(8);
+    assert_eq!("[\n  [\n  [1, 2]\n  ]\n]", xf.format("[[[1,2]]]").unwrap());
+}
+
+#[test]
+fn expand_to_depth() {
+    let input = "{\"a\": [1, {\"b\": [2, []]}, {}], \"c\": {\"d\": [3]}, \"e\": 4}";
+    let mut xf = Formatter::pretty_printer();
+    xf.expand_to_depth = Some(2);
+    xf.empty_style = jsonxf::EmptyStyle::Spaced;
+    let expected = "{\n  \"a\": [\n    1,\n    {\"b\":[2,[]]},\n    {}\n  ],\n  \
+                    \"c\": {\n    \"d\": [3]\n  },\n  \"e\": 4\n}";
+    assert_eq!(expected, xf.format(input).unwrap());
+
+    // The same, a byte at a time
+    let mut output: Vec<u8> = vec![];
+    xf.reset();
+    for b in input.as_bytes() {
+        xf.format_buf(&[*b], &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(expected.as_bytes(), &output[..]);
+
+    xf.expand_to_depth = Some(0);
+    assert_eq!(
+        "[1,{\"a\":2}]\n[]",
+        xf.format("[1, {\"a\": 2}] []").unwrap()
+    );
+
+    let mut xf = Formatter::pretty_printer();
+    xf.expand_to_depth = Some(1);
+    xf.sort_keys = true;
+    xf.max_inline_length = Some(10);
+    assert_eq!(
+        "[\n  {\"a\":1,\"b\":2},\n  [1, 2]\n]",
+        xf.format("[{\"b\": 2, \"a\": 1}, [1, 2]]").unwrap()
+    );
+}
+
+#[test]
+fn detect_indent() {
+    let input = "[]\n  {\n      \"a\": {\"b\": [1]},\n      \"c\": 2\n  }";
+    let expected =
+        "[]\n{\n    \"a\": {\n        \"b\": [\n            1\n        ]\n    },\n    \"c\": 2\n}";
+    let mut xf = Formatter::pretty_printer();
+    xf.detect_indent = true;
+    assert_eq!(expected, xf.format(input).unwrap());
+
+    // The same, a byte at a time
+    let mut output: Vec<u8> = vec![];
+    xf.reset();
+    for b in input.as_bytes() {
+        xf.format_buf(&[*b], &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(expected.as_bytes(), &output[..]);
+
+    // Each input is looked at afresh
+    assert_eq!("[\n  1\n]", xf.format("[1]").unwrap());
+    assert_eq!("\"a\"", xf.format("\"a\"").unwrap());
+
+    xf.sort_keys = true;
+    xf.max_inline_length = Some(10);
+    assert_eq!(
+        "{\n\t\"a\": [1, 2],\n\t\"b\": [\n\t\t\"abcdefgh\"\n\t]\n}",
+        xf.format("{\n\t\"b\": [\n\t\t\"abcdefgh\"],\n\t\"a\": [1,2]}")
+            .unwrap()
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.detect_indent = true;
+    assert_eq!("{\"a\":1}", xf.format("{\n    \"a\": 1\n}").unwrap());
+}
+
+#[test]
+fn add_trailing_commas() {
+    let mut xf = Formatter::pretty_printer();
+    xf.add_trailing_commas = true;
+    assert_eq!(
+        "[\n  1,\n  {},\n]\n[\n  2,\n]",
+        xf.format("[1,{},,] [2]").unwrap()
+    );
+
+    xf.max_inline_length = Some(6);
+    xf.sort_keys = true;
+    assert_eq!(
+        "{\n  \"a\": [1, 2],\n  \"b\": [\n    \"abc\",\n  ],\n}",
+        xf.format("{\"b\":[\"abc\",],\"a\":[1,2]}").unwrap()
+    );
+
+    let mut xf = Formatter::pretty_printer();
+    xf.add_trailing_commas = true;
+    xf.comment_mode = jsonxf::CommentMode::Preserve;
+    assert_eq!("[\n  1, // one\n]", xf.format("[1 // one\n]").unwrap());
+    assert_eq!("[\n  1,\n  // one\n]", xf.format("[1,\n// one\n]").unwrap());
+
+    let mut xf = Formatter::minimizer();
+    xf.add_trailing_commas = true;
+    assert_eq!("[1,[2]]", xf.format("[1, [2,],]").unwrap());
+}
@@ -0,0 +1,139 @@
+extern crate jsonxf;
+
+// A small deterministic generator of random JSON, so that these
+// properties get checked on far more inputs than could be written out.
+struct Gen(u64);
+
+impl Gen {
+    fn next(&mut self) -> u64 {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> usize {
+        (self.next() % n) as usize
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len() as u64)]
+    }
+
+    fn space(&mut self, out: &mut String) {
+        let s = self.pick(&["", "", "", " ", "\n", "\t", "\r\n  "]);
+        out.push_str(s);
+    }
+
+    fn string(&mut self, out: &mut String) {
+        out.push('"');
+        for _ in 0..self.below(6) {
+            let s = self.pick(&[
+                "a", " ", "\\\"", "\\\\", "\\n", "\\u00e9", "é", "{", "]", ",", ":", "/", "//",
+            ]);
+            out.push_str(s);
+        }
+        out.push('"');
+    }
+
+    fn value(&mut self, out: &mut String, depth: usize) {
+        let kind = if depth > 4 {
+            self.below(3)
+        } else {
+            self.below(5)
+        };
+        match kind {
+            0 => {
+                let s = self.pick(&["0", "-1", "12.5", "1e10", "true", "false", "null"]);
+                out.push_str(s);
+            }
+            1 => self.string(out),
+            2 => {
+                let s = self.pick(&["[]", "{}", "[ ]", "{\n}"]);
+                out.push_str(s);
+            }
+            3 => {
+                out.push('[');
+                for i in 0..self.below(4) {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.space(out);
+                    self.value(out, depth + 1);
+                    self.space(out);
+                }
+                out.push(']');
+            }
+            _ => {
+                out.push('{');
+                for i in 0..self.below(4) {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.space(out);
+                    self.string(out);
+                    self.space(out);
+                    out.push(':');
+                    self.space(out);
+                    self.value(out, depth + 1);
+                    self.space(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    // One or more top-level values separated by whitespace.
+    fn document(&mut self) -> String {
+        let mut out = String::new();
+        for _ in 0..1 + self.below(3) {
+            self.space(&mut out);
+            self.value(&mut out, 0);
+            out.push_str(self.pick(&[" ", "\n", "\t", "  \n"]));
+        }
+        out
+    }
+}
+
+fn check(input: &str) {
+    let min = jsonxf::minimize(input).unwrap();
+    let pretty = jsonxf::pretty_print(input).unwrap();
+    assert_eq!(jsonxf::minimize(&min).unwrap(), min, "input: {:?}", input);
+    assert_eq!(
+        jsonxf::minimize(&pretty).unwrap(),
+        min,
+        "input: {:?}",
+        input
+    );
+    assert_eq!(
+        jsonxf::pretty_print(&pretty).unwrap(),
+        pretty,
+        "input: {:?}",
+        input
+    );
+}
+
+#[test]
+fn minimize_is_idempotent_on_random_json() {
+    let mut gen = Gen(0x2545_f491_4f6c_dd1d);
+    for _ in 0..5000 {
+        check(&gen.document());
+    }
+}
+
+#[test]
+fn top_level_scalars_stay_separate() {
+    for input in &[
+        "1 2",
+        "1\n2",
+        "true false",
+        "\"a\" \"b\"",
+        "1 [2] 3",
+        "{} 1",
+    ] {
+        check(input);
+        let min = jsonxf::minimize(input).unwrap();
+        assert_eq!(min.split('\n').count(), input.split_whitespace().count());
+    }
+}
@@ -0,0 +1,72 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jsonxf-test-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("data.json");
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn entries(path: &Path) -> usize {
+    fs::read_dir(path.parent().unwrap()).unwrap().count()
+}
+
+#[test]
+fn formats_a_file() {
+    let path = temp_file("formats", "{\"a\": [1, 2]}\n");
+    jsonxf::format_file_in_place(&path, &mut Formatter::minimizer()).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":[1,2]}");
+    assert_eq!(entries(&path), 1);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn leaves_the_file_alone_on_error() {
+    let path = temp_file("error", "[1, [2");
+    let mut xf = Formatter::pretty_printer();
+    xf.strict = true;
+    assert!(jsonxf::format_file_in_place(&path, &mut xf).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1, [2");
+    assert_eq!(entries(&path), 1);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn missing_files_are_errors() {
+    let path = std::env::temp_dir().join("jsonxf-test-does-not-exist.json");
+    assert!(jsonxf::format_file_in_place(&path, &mut Formatter::minimizer()).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let path = temp_file("permissions", "[ 1 ]");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    jsonxf::format_file_in_place(&path, &mut Formatter::minimizer()).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1]");
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn formats_one_path_to_another() {
+    let src = temp_file("path-to-path", "[ {\"a\": 1} ]");
+    let dst = src.with_file_name("out.json");
+    let mut xf = Formatter::minimizer();
+    xf.buffer_capacity = 3;
+    jsonxf::format_path_to_path(&src, &dst, &mut xf).unwrap();
+    assert_eq!(fs::read_to_string(&dst).unwrap(), "[{\"a\":1}]");
+    assert_eq!(fs::read_to_string(&src).unwrap(), "[ {\"a\": 1} ]");
+
+    // The same file is formatted in place rather than truncated
+    let same = src.parent().unwrap().join(".").join("data.json");
+    jsonxf::format_path_to_path(&src, &same, &mut xf).unwrap();
+    assert_eq!(fs::read_to_string(&src).unwrap(), "[{\"a\":1}]");
+    fs::remove_dir_all(src.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,93 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn inline(max: usize, input: &str) -> String {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_inline_length = Some(max);
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn short_arrays_and_objects_fit_on_one_line() {
+    assert_eq!(inline(20, "[1,2,3]"), "[1, 2, 3]");
+    assert_eq!(inline(20, "{\"a\":1,\"b\":[]}"), "{\"a\": 1, \"b\": []}");
+    assert_eq!(inline(9, "[1,2,3]"), "[1, 2, 3]");
+    assert_eq!(inline(8, "[1,2,3]"), "[\n  1,\n  2,\n  3\n]");
+}
+
+#[test]
+fn nested_structures_collapse_inside_expanded_parents() {
+    assert_eq!(
+        inline(12, "{\"point\":[1,2],\"names\":[\"alpha\",\"beta\"],\"x\":{\"y\":{}}}"),
+        "{\n  \"point\": [1, 2],\n  \"names\": [\n    \"alpha\",\n    \"beta\"\n  ],\n  \"x\": {\"y\": {}}\n}"
+    );
+}
+
+#[test]
+fn counts_characters_not_bytes() {
+    assert_eq!(inline(10, "[\"é\",\"ü\"]"), "[\"é\", \"ü\"]");
+}
+
+#[test]
+fn multiple_records_and_other_options() {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_inline_length = Some(12);
+    xf.sort_keys = true;
+    xf.before_colon = String::from(" ");
+    assert_eq!(
+        xf.format("{\"b\":1,\"a\":2} [1,[2,3,4,5]]").unwrap(),
+        "{\n  \"a\" : 2,\n  \"b\" : 1\n}\n[\n  1,\n  [2, 3, 4, 5]\n]"
+    );
+}
+
+fn wrap(width: usize, input: &str) -> String {
+    let mut xf = Formatter::pretty_printer();
+    xf.wrap_width = Some(width);
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn wrap_width_breaks_only_what_does_not_fit() {
+    assert_eq!(wrap(9, "[1,2,3]"), "[1, 2, 3]");
+    assert_eq!(wrap(8, "[1,2,3]"), "[\n  1,\n  2,\n  3\n]");
+
+    // The comma after "a" counts; the last member has none
+    let input = "{\"aa\":[1,2],\"bb\":[3,4]}";
+    assert_eq!(
+        wrap(14, input),
+        "{\n  \"aa\": [\n    1,\n    2\n  ],\n  \"bb\": [3, 4]\n}"
+    );
+    assert_eq!(wrap(15, input), "{\n  \"aa\": [1, 2],\n  \"bb\": [3, 4]\n}");
+}
+
+#[test]
+fn wrap_width_counts_indentation() {
+    let input = "[[[1,2]],3]";
+    assert_eq!(wrap(11, input), "[\n  [[1, 2]],\n  3\n]");
+    let mut xf = Formatter::pretty_printer().with_spaces(4);
+    xf.wrap_width = Some(11);
+    assert_eq!(
+        xf.format(input).unwrap(),
+        "[\n    [\n        [\n            1,\n            2\n        ]\n    ],\n    3\n]"
+    );
+
+    let mut xf = Formatter::pretty_printer();
+    xf.wrap_width = Some(10);
+    xf.base_indent_level = 2;
+    assert_eq!(xf.format("[1,2]").unwrap(), "    [1, 2]");
+    assert_eq!(
+        xf.format("[1,22]").unwrap(),
+        "    [\n      1,\n      22\n    ]"
+    );
+}
+
+#[test]
+fn wrap_width_with_max_inline_length() {
+    let mut xf = Formatter::pretty_printer();
+    xf.wrap_width = Some(80);
+    xf.max_inline_length = Some(5);
+    assert_eq!(
+        xf.format("[[1,2],[3]]").unwrap(),
+        "[\n  [\n    1,\n    2\n  ],\n  [3]\n]"
+    );
+}
@@ -0,0 +1,241 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+use std::io::{self, Write};
+
+// Accepts at most one byte per call to `write`, as a pipe or socket
+// is allowed to.
+struct TrickleWriter {
+    output: Vec<u8>,
+}
+
+impl Write for TrickleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match buf.first() {
+            Some(&b) => {
+                self.output.push(b);
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn partial_writes_are_completed() {
+    let input = "{\"a\":[1,\"two\",{}],\"b\":12345}";
+    for xf in [Formatter::pretty_printer(), Formatter::minimizer()].iter_mut() {
+        let expected = xf.clone().format(input).unwrap();
+
+        let mut writer = TrickleWriter { output: vec![] };
+        xf.format_buf(input.as_bytes(), &mut writer).unwrap();
+        xf.finish(&mut writer).unwrap();
+        assert_eq!(expected.as_bytes(), &writer.output[..]);
+    }
+}
+
+// Returns at most `chunk` bytes per call to `read`.
+struct TrickleReader<'a> {
+    input: &'a [u8],
+    chunk: usize,
+}
+
+impl<'a> io::Read for TrickleReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.input.len()).min(self.chunk);
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn state_persists_across_reads() {
+    let input = "[{\"a\\\"[\": [1, 22, 333],\n \"b\": \"x\\\\\"}, 4444, true, null, {}]".repeat(50);
+    for xf in [Formatter::pretty_printer(), Formatter::minimizer()].iter_mut() {
+        let expected = xf.clone().format(&input).unwrap();
+
+        let mut reader = TrickleReader {
+            input: input.as_bytes(),
+            chunk: 7,
+        };
+        let mut output: Vec<u8> = vec![];
+        xf.format_stream(&mut reader, &mut output).unwrap();
+        assert_eq!(expected.as_bytes(), &output[..]);
+    }
+}
+
+#[test]
+fn readers_give_the_same_output_as_strings() {
+    let input = "[{\"a\": [1, 22]}, \"x\\\\\", null]\n\"b\"";
+    let reader = || TrickleReader {
+        input: input.as_bytes(),
+        chunk: 3,
+    };
+    let pretty = jsonxf::pretty_print(input).unwrap();
+    assert_eq!(jsonxf::pretty_print_reader(reader()).unwrap(), pretty);
+    assert_eq!(
+        jsonxf::pretty_print_reader_bytes(reader()).unwrap(),
+        pretty.as_bytes()
+    );
+    let minimized = jsonxf::minimize(input).unwrap();
+    assert_eq!(jsonxf::minimize_reader(reader()).unwrap(), minimized);
+    assert_eq!(
+        jsonxf::minimize_reader_bytes(reader()).unwrap(),
+        minimized.as_bytes()
+    );
+
+    let invalid: &[u8] = b"[\"\xff\"]";
+    assert!(matches!(
+        jsonxf::minimize_reader(invalid),
+        Err(jsonxf::JsonxfError::Utf8(_))
+    ));
+}
+
+// Records how much had been written each time it was flushed.
+struct FlushRecorder {
+    output: Vec<u8>,
+    flushed_at: Vec<usize>,
+}
+
+impl Write for FlushRecorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed_at.push(self.output.len());
+        Ok(())
+    }
+}
+
+#[test]
+fn flushes_after_each_document() {
+    let input = "{\"a\": [1]} \"b\" 2 [";
+    for &flush in [false, true].iter() {
+        let mut xf = Formatter::pretty_printer();
+        xf.eager_record_separators = true;
+        xf.flush_after_documents = flush;
+        let mut output = FlushRecorder {
+            output: vec![],
+            flushed_at: vec![],
+        };
+        xf.format_stream(&mut input.as_bytes(), &mut output)
+            .unwrap();
+        let output_len = output.output.len();
+        if flush {
+            assert_eq!(output.flushed_at, vec![23, 27, 29, output_len]);
+        } else {
+            assert_eq!(output.flushed_at, vec![output_len]);
+        }
+    }
+}
+
+#[test]
+fn escapes_persist_across_one_byte_reads() {
+    let inputs = [
+        "[\"a\\\"\", \"b\"]",
+        "{\"a\\\\\": \"\\\"}\"}",
+        "\"\\\\\" \"\\\"\"",
+        "[\"\\u0022\\\\\\\"\"]",
+    ];
+    for input in inputs.iter() {
+        for xf in [Formatter::pretty_printer(), Formatter::minimizer()].iter_mut() {
+            let expected = xf.clone().format(input).unwrap();
+
+            let mut reader = TrickleReader {
+                input: input.as_bytes(),
+                chunk: 1,
+            };
+            let mut output: Vec<u8> = vec![];
+            xf.format_stream(&mut reader, &mut output).unwrap();
+            assert_eq!(expected.as_bytes(), &output[..]);
+        }
+    }
+    assert_eq!(jsonxf::minimize(inputs[0]).unwrap(), "[\"a\\\"\",\"b\"]");
+}
+
+// Produces `["` and `count` copies of `piece`, then `"]`, without
+// holding them all in memory.
+struct HugeStringReader {
+    piece: &'static [u8],
+    count: usize,
+    pos: usize,
+}
+
+impl io::Read for HugeStringReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let total = self.piece.len() * self.count + 4;
+        let mut n = 0;
+        while n < buf.len() && self.pos < total {
+            buf[n] = match self.pos {
+                0 => b'[',
+                1 => b'"',
+                p if p == total - 2 => b'"',
+                p if p == total - 1 => b']',
+                p => self.piece[(p - 2) % self.piece.len()],
+            };
+            n += 1;
+            self.pos += 1;
+        }
+        Ok(n)
+    }
+}
+
+#[test]
+fn huge_strings_are_streamed() {
+    let mut xf = Formatter::minimizer();
+    xf.ascii_only = true;
+    xf.escape_slashes = true;
+    xf.remove_needless_escapes = true;
+    xf.canonicalize_string_whitespace = true;
+    let count = 1 << 20;
+    let mut reader = HugeStringReader {
+        piece: "a/é\t\\u0041".as_bytes(),
+        count,
+        pos: 0,
+    };
+    let stats = xf
+        .format_stream_with_stats(&mut reader, &mut io::sink())
+        .unwrap();
+    assert_eq!(stats.input_bytes, 11 * count + 4);
+    assert_eq!(stats.output_bytes, 12 * count + 4);
+    assert_eq!(stats.longest_string, 7 * count);
+}
+
+#[test]
+fn progress_is_reported_every_interval() {
+    let input = "[1, 2, 3]".repeat(10);
+    let reader = TrickleReader {
+        input: input.as_bytes(),
+        chunk: 7,
+    };
+    let mut output: Vec<u8> = vec![];
+    let mut seen: Vec<u64> = vec![];
+    Formatter::minimizer()
+        .format_stream_with_progress(reader, &mut output, 20, |n| seen.push(n))
+        .unwrap();
+    assert_eq!(output, "[1,2,3]\n".repeat(10).trim_end().as_bytes());
+    assert_eq!(seen, vec![21, 42, 63, 84, 90]);
+
+    // The end of the input is reported once, even on an interval
+    let reader = TrickleReader {
+        input: input.as_bytes(),
+        chunk: 10,
+    };
+    seen.clear();
+    Formatter::minimizer()
+        .format_stream_with_progress(reader, &mut output, 30, |n| seen.push(n))
+        .unwrap();
+    assert_eq!(seen, vec![30, 60, 90]);
+
+    seen.clear();
+    Formatter::minimizer()
+        .format_stream_with_progress(&b""[..], &mut output, 30, |n| seen.push(n))
+        .unwrap();
+    assert_eq!(seen, vec![0]);
+}
@@ -0,0 +1,119 @@
+extern crate jsonxf;
+use jsonxf::{canonicalize_jcs, JsonxfError};
+
+fn number(input: &str) -> String {
+    canonicalize_jcs(input).unwrap()
+}
+
+fn reason(input: &str) -> String {
+    match canonicalize_jcs(input) {
+        Err(JsonxfError::NotCanonicalizable { reason }) => reason,
+        other => panic!("expected NotCanonicalizable, got {:?}", other),
+    }
+}
+
+#[test]
+fn writes_numbers_as_ecmascript_does() {
+    // From Appendix B of RFC 8785
+    assert_eq!(number("0"), "0");
+    assert_eq!(number("-0"), "0");
+    assert_eq!(number("5e-324"), "5e-324");
+    assert_eq!(number("-5e-324"), "-5e-324");
+    assert_eq!(number("1.7976931348623157e308"), "1.7976931348623157e+308");
+    assert_eq!(number("9007199254740992"), "9007199254740992");
+    assert_eq!(number("9007199254740993"), "9007199254740992");
+    assert_eq!(number("295147905179352830000"), "295147905179352830000");
+    assert_eq!(number("9.999999999999997e22"), "9.999999999999997e+22");
+    assert_eq!(number("1e23"), "1e+23");
+    assert_eq!(number("999999999999999700000"), "999999999999999700000");
+    assert_eq!(number("999999999999999900000"), "999999999999999900000");
+    assert_eq!(number("1e21"), "1e+21");
+    assert_eq!(number("9.999999999999997e-7"), "9.999999999999997e-7");
+    assert_eq!(number("0.000001"), "0.000001");
+    assert_eq!(number("333333333.3333333"), "333333333.3333333");
+    assert_eq!(number("1424953923781206.2"), "1424953923781206.2");
+}
+
+#[test]
+fn shortens_number_spellings() {
+    assert_eq!(
+        number("[1.0, 1.50, 10E1, 2e-1, -1e-7]"),
+        "[1,1.5,100,0.2,-1e-7]"
+    );
+}
+
+#[test]
+fn sorts_keys_by_utf16_code_units() {
+    // From section 3.2.3 of RFC 8785
+    let input = "{\"\\u20ac\": \"Euro Sign\", \"\\r\": \"Carriage Return\", \
+                 \"\\ufb33\": \"Hebrew Letter Dalet With Dagesh\", \"1\": \"One\", \
+                 \"\\ud83d\\ude00\": \"Emoji: Grinning Face\", \"\\u0080\": \"Control\", \
+                 \"\\u00f6\": \"Latin Small Letter O With Diaeresis\"}";
+    assert_eq!(
+        canonicalize_jcs(input).unwrap(),
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+         \"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+         \"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+    );
+}
+
+#[test]
+fn writes_only_required_string_escapes() {
+    assert_eq!(
+        canonicalize_jcs("[\"\\u0041\\/\\u001f\\u000A\\\"\\\\\", \"\u{e9}\"]").unwrap(),
+        "[\"A/\\u001f\\n\\\"\\\\\",\"\u{e9}\"]"
+    );
+}
+
+#[test]
+fn sorts_nested_objects() {
+    assert_eq!(
+        canonicalize_jcs("{\"b\": {\"d\": 1, \"c\": [{\"f\": null, \"e\": true}]}, \"a\": false}")
+            .unwrap(),
+        "{\"a\":false,\"b\":{\"c\":[{\"e\":true,\"f\":null}],\"d\":1}}"
+    );
+}
+
+#[test]
+fn rejects_input_without_a_canonical_form() {
+    assert_eq!(reason("1e400"), "invalid number `1e400`");
+    assert_eq!(reason("[01]"), "invalid number `01`");
+    assert_eq!(reason("[1.]"), "invalid number `1.`");
+    assert_eq!(reason("nan"), "invalid literal `nan`");
+    assert_eq!(reason("\"\\ud800\""), "invalid string `\"\\ud800\"`");
+    assert_eq!(reason("{\"a\": 1, \"\\u0061\": 2}"), "duplicate key \"a\"");
+    assert_eq!(reason("{\"a\"}"), "key \"a\" has no value");
+    assert_eq!(reason("1 2"), "expected one value, found 2");
+    assert_eq!(reason(""), "expected one value, found 0");
+}
+
+#[test]
+fn reports_malformed_structure_as_strict_mode_does() {
+    assert!(matches!(
+        canonicalize_jcs("[1, 2"),
+        Err(JsonxfError::UnexpectedEnd { .. })
+    ));
+}
+
+#[test]
+fn rejects_misplaced_commas_and_colons() {
+    assert_eq!(reason("[1,]"), "unexpected `]`");
+    assert_eq!(reason("[,1]"), "unexpected `,`");
+    assert_eq!(reason("[1,,2]"), "unexpected `,`");
+    assert_eq!(reason("[1:2]"), "unexpected `:`");
+    assert_eq!(reason("[true false]"), "unexpected `false`");
+    assert_eq!(reason("{\"a\":1 \"b\":2}"), "unexpected `\"b\"`");
+    assert_eq!(reason("{\"a\":1,}"), "unexpected `}`");
+    assert_eq!(reason("{,\"a\":1}"), "unexpected `,`");
+    assert_eq!(reason("{\"a\"::1}"), "unexpected `:`");
+    assert_eq!(reason("{\"a\":}"), "unexpected `}`");
+    assert_eq!(reason("{\"a\" 1}"), "key \"a\" has no value");
+    assert_eq!(reason("{1:2}"), "unexpected `1`");
+    assert_eq!(reason(",1"), "unexpected `,`");
+    assert_eq!(reason("1,"), "unexpected `,`");
+    assert_eq!(reason("[1] [2],"), "unexpected `,`");
+    assert_eq!(
+        canonicalize_jcs("[{\"a\": [], \"b\": {}}, [[]]]").unwrap(),
+        "[{\"a\":[],\"b\":{}},[[]]]"
+    );
+}
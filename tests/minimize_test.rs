@@ -36,3 +36,61 @@ fn minimize_passes_test_cases() {
         assert_eq!(jsonxf::minimize(input).unwrap(), output);
     }
 }
+
+#[test]
+fn long_scalars_across_buffers() {
+    let number = "1234567890".repeat(5000);
+    let input = format!("[{}, {},true,null]", number, number);
+    let output = format!("[{},{},true,null]", number, number);
+    assert_eq!(output, jsonxf::minimize(&input).unwrap());
+}
+
+#[test]
+fn minimize_in_place() {
+    let test_cases = vec![
+        "{ \"a\": [ 1, 2 ], \"b\" : \"x y\" }\n",
+        "[1][2]{}{}\"a\"3 4",
+        "",
+        "  \n",
+    ];
+    for input in test_cases {
+        let mut json = input.as_bytes().to_vec();
+        jsonxf::minimize_in_place(&mut json).unwrap();
+        assert_eq!(jsonxf::minimize(input).unwrap().as_bytes(), &json[..]);
+    }
+
+    // Output overtaking input across chunk boundaries
+    let input = "[1]".repeat(20000);
+    let mut json = input.as_bytes().to_vec();
+    jsonxf::minimize_in_place(&mut json).unwrap();
+    assert_eq!(jsonxf::minimize(&input).unwrap().as_bytes(), &json[..]);
+
+    let input = format!("[{}]", "  1 ,\n".repeat(20000));
+    let mut json = input.as_bytes().to_vec();
+    jsonxf::minimize_in_place(&mut json).unwrap();
+    assert_eq!(jsonxf::minimize(&input).unwrap().as_bytes(), &json[..]);
+}
+
+#[test]
+fn deep_nesting() {
+    let depth = 200_000;
+    let input = format!("{}1,2{}", "[".repeat(depth), "]".repeat(depth));
+    assert_eq!(input, jsonxf::minimize(&input).unwrap());
+}
+
+#[test]
+fn trailing_newline() {
+    // None by default
+    assert_eq!(jsonxf::minimize("[1]\n").unwrap(), "[1]");
+
+    let mut xf = jsonxf::Formatter::minimizer();
+    xf.trailing_output = String::from("\n");
+    let mut input: &[u8] = b"{ \"a\": 1 }\n\n{ \"b\": 2 }\n\n";
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream(&mut input, &mut output).unwrap();
+    assert_eq!(output, b"{\"a\":1}\n{\"b\":2}\n");
+
+    // Exactly one, even for empty input
+    assert_eq!(xf.format("  \n").unwrap(), "\n");
+    assert_eq!(xf.format("\"x\"\n\n").unwrap(), "\"x\"\n");
+}
@@ -0,0 +1,57 @@
+extern crate jsonxf;
+use jsonxf::{BlankLines, Formatter};
+
+fn ndjson(xf: &mut Formatter, input: &str) -> String {
+    let mut input = input.as_bytes();
+    let mut output: Vec<u8> = vec![];
+    xf.format_ndjson_stream(&mut input, &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn pretty_prints_each_line() {
+    let mut input: &[u8] = b"{\"a\":1}\n[1,2]\r\n\n  \"x\"";
+    let mut output: Vec<u8> = vec![];
+    jsonxf::pretty_print_ndjson_stream(&mut input, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\n  \"a\": 1\n}\n[\n  1,\n  2\n]\n\"x\""
+    );
+}
+
+#[test]
+fn minimizes_each_line() {
+    let mut xf = Formatter::minimizer();
+    xf.eager_record_separators = true;
+    assert_eq!(
+        ndjson(&mut xf, "{ \"a\" : 1 }\n[ 1, 2 ]\n"),
+        "{\"a\":1}\n[1,2]\n"
+    );
+}
+
+#[test]
+fn broken_lines_do_not_leak() {
+    let mut xf = Formatter::minimizer();
+    assert_eq!(
+        ndjson(&mut xf, "{\"a\":[1\n\"b\"\n[\"unterminated\n2"),
+        "{\"a\":[1\n\"b\"\n[\"unterminated\n\n2"
+    );
+}
+
+#[test]
+fn blank_lines() {
+    let input = "1\n\n \t\n2\n";
+
+    let mut xf = Formatter::minimizer();
+    assert_eq!(ndjson(&mut xf, input), "1\n2");
+
+    let mut xf = Formatter::minimizer();
+    xf.blank_lines = BlankLines::Keep;
+    assert_eq!(ndjson(&mut xf, input), "1\n\n\n2");
+
+    let mut xf = Formatter::minimizer();
+    xf.blank_lines = BlankLines::Keep;
+    xf.record_separator = String::from("\r\n");
+    xf.trailing_output = String::from("\r\n");
+    assert_eq!(ndjson(&mut xf, input), "1\r\n\r\n\r\n2\r\n");
+}
@@ -0,0 +1,51 @@
+// Run with `cargo test --no-default-features --test no_std_test`.
+#![cfg(not(feature = "std"))]
+
+extern crate jsonxf;
+use jsonxf::io::{Error, Write};
+use jsonxf::{Formatter, JsonxfError};
+
+// Holds at most `capacity` bytes, like a buffer of fixed size.
+struct FixedBuffer {
+    output: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for FixedBuffer {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if self.output.len() + buf.len() > self.capacity {
+            return Err(Error::new("buffer full"));
+        }
+        self.output.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[test]
+fn write_errors_are_reported() {
+    let mut xf = Formatter::pretty_printer();
+    let mut output = FixedBuffer {
+        output: vec![],
+        capacity: 8,
+    };
+    let result = xf
+        .format_buf(b"[1, 2, 3]", &mut output)
+        .and_then(|_| xf.finish(&mut output));
+    match result {
+        Err(JsonxfError::Io(ref e)) => assert_eq!(e.message(), "buffer full"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(output.output, b"[\n  1,\n");
+}
+
+#[test]
+fn output_which_fits_is_written() {
+    let mut xf = Formatter::minimizer();
+    let mut output = FixedBuffer {
+        output: vec![],
+        capacity: 7,
+    };
+    xf.format_buf(b"[1, 2, 3]", &mut output).unwrap();
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output.output, b"[1,2,3]");
+}
@@ -0,0 +1,133 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn normalize(input: &str) -> String {
+    let mut xf = Formatter::minimizer();
+    xf.normalize_numbers = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn normalizes_numbers() {
+    let test_cases = vec![
+        ("1.0", "1"),
+        ("0.5000", "0.5"),
+        ("+5", "5"),
+        ("-0.0", "-0"),
+        ("1E10", "1e10"),
+        ("1e+10", "1e10"),
+        ("2.50E-007", "2.5e-7"),
+        ("3e0", "3"),
+        ("10", "10"),
+        ("-12.030e-00", "-12.03"),
+    ];
+    for (input, output) in test_cases {
+        assert_eq!(output, normalize(input));
+    }
+}
+
+#[test]
+fn leaves_everything_else_alone() {
+    assert_eq!(
+        normalize("[true, null, false, \"1.0E+5\", 1., .5, 1e, 0x10, +, 1.5.0]"),
+        "[true,null,false,\"1.0E+5\",1.,.5,1e,0x10,+,1.5.0]"
+    );
+    assert_eq!(normalize("{\"1.0\": 2.0}"), "{\"1.0\":2}");
+}
+
+#[test]
+fn normalizes_across_buffers_and_records() {
+    let mut xf = Formatter::pretty_printer();
+    xf.normalize_numbers = true;
+    xf.eager_record_separators = true;
+    let mut output: Vec<u8> = vec![];
+    for chunk in b"[1.500, +2E+1] 3.000".chunks(1) {
+        xf.format_buf(chunk, &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"[\n  1.5,\n  2e1\n]\n3\n");
+}
+
+#[test]
+fn adjacent_scalars_stay_apart() {
+    assert_eq!(
+        jsonxf::minimize("[NaN Infinity, 1\n2]").unwrap(),
+        "[NaN Infinity,1 2]"
+    );
+    assert_eq!(
+        jsonxf::pretty_print("[NaN  -Infinity]").unwrap(),
+        "[\n  NaN -Infinity\n]"
+    );
+    assert_eq!(jsonxf::minimize("[1 , 2]").unwrap(), "[1,2]");
+}
+
+#[test]
+fn nonfinite_to_null() {
+    let mut xf = Formatter::minimizer();
+    xf.nonfinite_to_null = true;
+    assert_eq!(
+        xf.format("{\"a\": NaN, \"b\": [Infinity, -Infinity, \"NaN\", NaNa, 1.50]}")
+            .unwrap(),
+        "{\"a\":null,\"b\":[null,null,\"NaN\",NaNa,1.50]}"
+    );
+    xf.normalize_numbers = true;
+    assert_eq!(xf.format("[-Infinity, 1.50]").unwrap(), "[null,1.5]");
+}
+
+fn validate(input: &str) -> Result<String, jsonxf::JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    xf.validate_numbers = true;
+    xf.format(input)
+}
+
+#[test]
+fn validate_numbers_keeps_valid_numbers_unchanged() {
+    assert_eq!(
+        "[0,-0,1.50,1E+02,-0.000e-0,123456789012345678901234567890,true]",
+        validate("[0, -0, 1.50, 1E+02, -0.000e-0, 123456789012345678901234567890, true]").unwrap()
+    );
+}
+
+#[test]
+fn validate_numbers_reports_offsets() {
+    for (input, offset) in vec![
+        ("[1, 1.2.3]", 4),
+        ("--5", 0),
+        ("{\"a\": 1e}", 6),
+        ("[01]", 1),
+        ("[.5, 1]", 1),
+        ("[1, +1]", 4),
+        ("[-]", 1),
+        ("[1.]", 1),
+        ("[1e+]", 1),
+        ("[NaN, -Infinity]", 6),
+    ] {
+        match validate(input) {
+            Err(jsonxf::JsonxfError::InvalidNumber { offset: found }) => {
+                assert_eq!(offset, found, "{}", input)
+            }
+            other => panic!("{}: {:?}", input, other),
+        }
+    }
+}
+
+#[test]
+fn validate_numbers_with_other_options() {
+    let mut xf = Formatter::minimizer();
+    xf.validate_numbers = true;
+    xf.nonfinite_to_null = true;
+    assert_eq!("[null,2.0]", xf.format("[-Infinity, 2.0]").unwrap());
+
+    xf.sort_keys = true;
+    assert!(matches!(
+        xf.format("{\"b\": 1, \"a\": 0x10}"),
+        Err(jsonxf::JsonxfError::InvalidNumber { offset: 14 })
+    ));
+
+    let mut xf = Formatter::minimizer();
+    xf.validate_numbers = true;
+    let mut output: Vec<u8> = vec![];
+    xf.format_buf(b"[12", &mut output).unwrap();
+    xf.format_buf(b"3.4", &mut output).unwrap();
+    assert!(xf.format_buf(b".5]", &mut output).is_err());
+}
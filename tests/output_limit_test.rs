@@ -0,0 +1,110 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, JsonxfError};
+use std::io::{self, Write};
+
+// Counts the bytes written to it, and how often it is flushed.
+#[derive(Default)]
+struct CountingWriter {
+    bytes: usize,
+    flushes: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn output_within_the_limit_is_written() {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_output_bytes = Some(12);
+    assert_eq!(xf.format("[1,2]").unwrap(), "[\n  1,\n  2\n]");
+    xf.max_output_bytes = Some(11);
+    assert!(xf.format("[1,2]").is_err());
+}
+
+#[test]
+fn output_is_cut_off_at_the_limit() {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_output_bytes = Some(6);
+    let mut output: Vec<u8> = vec![];
+    let mut input: &[u8] = b"[1,2,3]";
+    match xf.format_stream(&mut input, &mut output) {
+        Err(JsonxfError::OutputTooLarge { limit: 6 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(output, b"[\n  1,");
+}
+
+#[test]
+fn formatting_stops_at_the_limit() {
+    // Each document is flushed, so the flushes show how far it got
+    let input = "1 ".repeat(100_000);
+    let mut xf = Formatter::minimizer();
+    xf.flush_after_documents = true;
+    xf.max_output_bytes = Some(10);
+    let mut output = CountingWriter::default();
+    match xf.format_buf(input.as_bytes(), &mut output) {
+        Err(JsonxfError::OutputTooLarge { limit: 10 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(output.bytes, 10);
+    assert_eq!(output.flushes, 5);
+}
+
+#[test]
+fn counts_output_across_calls() {
+    let mut xf = Formatter::minimizer();
+    xf.max_output_bytes = Some(4);
+    let mut output: Vec<u8> = vec![];
+    xf.format_buf(b"[1, ", &mut output).unwrap();
+    xf.format_buf(b"2", &mut output).unwrap();
+    let err = xf.format_buf(b", 3]", &mut output).unwrap_err();
+    assert_eq!(err.to_string(), "output exceeds the limit of 4 bytes");
+    assert_eq!(output, b"[1,2");
+
+    // The count starts again with the next document
+    xf.reset();
+    output.clear();
+    xf.format_buf(b"[5]", &mut output).unwrap();
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"[5]");
+}
+
+#[test]
+fn trailing_output_counts() {
+    let mut xf = Formatter::minimizer();
+    xf.trailing_output = String::from("\n");
+    xf.max_output_bytes = Some(3);
+    assert!(xf.format("[1]").is_err());
+    xf.max_output_bytes = Some(4);
+    assert_eq!(xf.format("[1]").unwrap(), "[1]\n");
+}
+
+#[test]
+fn works_with_buffered_options() {
+    let mut xf = Formatter::pretty_printer();
+    xf.sort_keys = true;
+    xf.max_output_bytes = Some(10);
+    match xf.format("{\"b\":1,\"a\":2}") {
+        Err(JsonxfError::OutputTooLarge { limit: 10 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn applies_to_a_whole_ndjson_stream() {
+    let mut xf = Formatter::minimizer();
+    xf.max_output_bytes = Some(8);
+    let mut input: &[u8] = b"[1]\n[2]\n[3]\n";
+    let mut output: Vec<u8> = vec![];
+    assert!(xf.format_ndjson_stream(&mut input, &mut output).is_err());
+    assert_eq!(output, b"[1]\n[2]\n");
+}
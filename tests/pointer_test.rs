@@ -0,0 +1,56 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, JsonxfError};
+
+const DOC: &str = "{\"results\": [{\"data\": {\"x\": 1}}, 2], \
+                   \"a/b\": 3, \"m~n\": 4, \"\": 5, \"\\u00e9\\n\": 6, \"dup\": 7, \"dup\": 8}";
+
+fn find(pointer: &str) -> Result<String, JsonxfError> {
+    Formatter::minimizer().format_pointer(DOC, pointer)
+}
+
+#[test]
+fn follows_pointers() {
+    let test_cases = vec![
+        ("/results/0/data", "{\"x\":1}"),
+        ("/results/0/data/x", "1"),
+        ("/results/1", "2"),
+        ("/a~1b", "3"),
+        ("/m~0n", "4"),
+        ("/", "5"),
+        ("/é\n", "6"),
+        ("/dup", "8"),
+    ];
+    for (pointer, output) in test_cases {
+        assert_eq!(output, find(pointer).unwrap());
+    }
+    assert_eq!(jsonxf::minimize(DOC).unwrap(), find("").unwrap());
+}
+
+#[test]
+fn reports_missing_values() {
+    for pointer in &[
+        "/nope",
+        "/results/2",
+        "/results/01",
+        "/results/-",
+        "/a~1b/c",
+        "results",
+    ] {
+        match find(pointer) {
+            Err(JsonxfError::PointerNotFound { pointer: ref p }) if p == pointer => {}
+            other => panic!("unexpected result for {:?}: {:?}", pointer, other),
+        }
+    }
+    assert_eq!(
+        find("/x").unwrap_err().to_string(),
+        "JSON pointer \"/x\" does not match anything"
+    );
+}
+
+#[test]
+fn pretty_prints_pointed_at_value() {
+    assert_eq!(
+        jsonxf::pretty_print_pointer(DOC, "/results/0", "    ").unwrap(),
+        "{\n    \"data\": {\n        \"x\": 1\n    }\n}"
+    );
+}
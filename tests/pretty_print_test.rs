@@ -70,3 +70,51 @@ fn eager_record_separators() {
         assert_eq!(printer.format(input).unwrap(), output);
     }
 }
+
+#[test]
+fn eager_record_separators_after_scalars() {
+    let test_cases = vec![
+        ("42", "42\n"),
+        ("\"a\"", "\"a\"\n"),
+        ("true\n", "true\n"),
+        ("null [1]", "null\n[\n  1\n]\n"),
+        ("[1] 2", "[\n  1\n]\n2\n"),
+        ("{\"a\": 1}", "{\n  \"a\": 1\n}\n"),
+    ];
+    for (input, output) in test_cases {
+        let mut printer = jsonxf::Formatter::pretty_printer();
+        printer.eager_record_separators = true;
+        assert_eq!(printer.format(input).unwrap(), output);
+    }
+}
+
+#[test]
+fn concatenated_documents() {
+    assert_eq!("{}\n{}", jsonxf::pretty_print("{}{}").unwrap());
+    assert_eq!(
+        "{\n  \"a\": 1\n}\n[\n  2\n]\n3\n\"x\"",
+        jsonxf::pretty_print("{\"a\":1}[2] 3\"x\"").unwrap()
+    );
+
+    // One blank line between documents, and a newline after the last
+    let mut xf = jsonxf::Formatter::pretty_printer();
+    xf.record_separator = String::from("\n\n");
+    xf.trailing_output = String::from("\n");
+    assert_eq!(
+        "{\n  \"a\": [\n    {}\n  ]\n}\n\n{\n  \"b\": 2\n}\n",
+        xf.format("{\"a\":[{}]}\n{\"b\":2}").unwrap()
+    );
+}
+
+#[test]
+fn empty_indent_puts_one_token_per_line() {
+    let mut xf = jsonxf::Formatter::pretty_printer();
+    xf.indent = String::from("");
+    assert_eq!(
+        xf.format("{\"a\": [1, [], {}, {\"b\": [2]}], \"c\": null}")
+            .unwrap(),
+        "{\n\"a\": [\n1,\n[],\n{},\n{\n\"b\": [\n2\n]\n}\n],\n\"c\": null\n}"
+    );
+    xf.empty_style = jsonxf::EmptyStyle::Expanded;
+    assert_eq!(xf.format("[[], {}]").unwrap(), "[\n[\n],\n{\n}\n]");
+}
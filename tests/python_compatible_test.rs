@@ -0,0 +1,37 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+// Expected outputs are from Python 3's `json.dumps(json.loads(input), indent=...)`.
+
+#[test]
+fn matches_json_dumps() {
+    let mut xf = Formatter::python_compatible(2);
+    assert_eq!(
+        xf.format(
+            "{\"name\":\"caf\u{e9}\",\"tags\":[\"a\",\"b\"],\"nested\":{\"empty_list\":[],\
+             \"empty_obj\":{},\"n\":[1,2.5,-3e-05,null,true,false]}}"
+        )
+        .unwrap(),
+        "{\n  \"name\": \"caf\\u00e9\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ],\n  \
+         \"nested\": {\n    \"empty_list\": [],\n    \"empty_obj\": {},\n    \"n\": [\n      \
+         1,\n      2.5,\n      -3e-05,\n      null,\n      true,\n      false\n    ]\n  }\n}"
+    );
+}
+
+#[test]
+fn matches_json_dumps_with_other_indents() {
+    let mut xf = Formatter::python_compatible(4);
+    assert_eq!(
+        xf.format("[[1, [2, {\"k\": \"\\ud83d\\ude00\"}]]]")
+            .unwrap(),
+        "[\n    [\n        1,\n        [\n            2,\n            {\n                \
+         \"k\": \"\\ud83d\\ude00\"\n            }\n        ]\n    ]\n]"
+    );
+    assert_eq!(
+        xf.format("[\"\u{1f600} a\\/b\"]").unwrap(),
+        "[\n    \"\\ud83d\\ude00 a/b\"\n]"
+    );
+
+    let mut xf = Formatter::python_compatible(0);
+    assert_eq!(xf.format("{\"a\": {}}").unwrap(), "{\n\"a\": {}\n}");
+}
@@ -0,0 +1,74 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn redact(mut xf: Formatter, keys: &[&str], input: &str) -> String {
+    xf.redact_keys = keys.iter().map(|&key| String::from(key)).collect();
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn redacts_strings_at_any_depth() {
+    assert_eq!(
+        redact(
+            Formatter::minimizer(),
+            &["token", "password"],
+            "[{\"token\": \"abc\", \"a\": {\"password\": \"x\\\"y\"}}, {\"password\": null}]"
+        ),
+        "[{\"token\":\"***\",\"a\":{\"password\":\"***\"}},{\"password\":null}]"
+    );
+}
+
+#[test]
+fn compares_decoded_keys() {
+    assert_eq!(
+        redact(
+            Formatter::minimizer(),
+            &["key"],
+            "{\"k\\u0065y\": \"1\", \"Key\": \"2\", \"key \": \"3\"}"
+        ),
+        "{\"k\\u0065y\":\"***\",\"Key\":\"2\",\"key \":\"3\"}"
+    );
+}
+
+#[test]
+fn redacts_other_values_when_asked() {
+    let mut xf = Formatter::pretty_printer();
+    xf.redact_non_strings = true;
+    assert_eq!(
+        redact(
+            xf,
+            &["secret"],
+            "{\"secret\": [1, {\"secret\": 2}], \"other\": {\"secret\": false}}"
+        ),
+        "{\n  \"secret\": \"***\",\n  \"other\": {\n    \"secret\": \"***\"\n  }\n}"
+    );
+}
+
+#[test]
+fn leaves_keys_and_array_elements_alone() {
+    assert_eq!(
+        redact(
+            Formatter::pretty_printer(),
+            &["password"],
+            "[\"password\", {\"password\": \"a\", \"b\": \"password\"}]"
+        ),
+        "[\n  \"password\",\n  {\n    \"password\": \"***\",\n    \"b\": \"password\"\n  }\n]"
+    );
+}
+
+#[test]
+fn works_with_streams_and_other_options() {
+    let mut xf = Formatter::minimizer();
+    xf.redact_keys = vec![String::from("b")];
+    xf.sort_keys = true;
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream(
+        &mut &b"{\"b\": \"x\", \"a\": 1} {\"b\": \"y\"}"[..],
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\"a\":1,\"b\":\"***\"}\n{\"b\":\"***\"}"
+    );
+}
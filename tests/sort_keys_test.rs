@@ -0,0 +1,83 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn sorted(mut xf: Formatter, input: &str) -> String {
+    xf.sort_keys = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn sorts_nested_objects() {
+    assert_eq!(
+        sorted(
+            Formatter::minimizer(),
+            "{\"b\": {\"y\": 1, \"x\": [3, 1, 2]}, \"a\": null, \"c\": \"}{\"}"
+        ),
+        "{\"a\":null,\"b\":{\"x\":[3,1,2],\"y\":1},\"c\":\"}{\"}"
+    );
+}
+
+#[test]
+fn sorts_objects_in_arrays() {
+    assert_eq!(
+        sorted(
+            Formatter::minimizer(),
+            "[{\"b\": 1, \"a\": 2}, [{\"d\": {}, \"c\": []}]]"
+        ),
+        "[{\"a\":2,\"b\":1},[{\"c\":[],\"d\":{}}]]"
+    );
+}
+
+#[test]
+fn sorts_by_bytes_and_keeps_duplicates_in_order() {
+    assert_eq!(
+        sorted(
+            Formatter::minimizer(),
+            "{\"é\": 1, \"z\": 2, \"Z\": 3, \"z\": 4}"
+        ),
+        "{\"Z\":3,\"z\":2,\"z\":4,\"é\":1}"
+    );
+}
+
+#[test]
+fn pretty_prints_sorted_records() {
+    let mut xf = Formatter::pretty_printer();
+    xf.eager_record_separators = true;
+    assert_eq!(
+        sorted(xf, "{\"b\":1,\"a\":[]} 2 {\"d\":3,\"c\":4}"),
+        "{\n  \"a\": [],\n  \"b\": 1\n}\n2\n{\n  \"c\": 4,\n  \"d\": 3\n}\n"
+    );
+}
+
+#[test]
+fn applies_input_options_before_sorting() {
+    let mut xf = Formatter::minimizer();
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    xf.remove_trailing_commas = true;
+    assert_eq!(
+        sorted(xf, "{\"b\": 1, // b\n \"a\": 2, }"),
+        "{\"a\":2,\"b\":1}"
+    );
+}
+
+#[test]
+fn deep_nesting() {
+    let input = format!("{}{}", "[".repeat(2_000), "]".repeat(2_000));
+    assert_eq!(sorted(Formatter::minimizer(), &input), input);
+}
+
+#[test]
+fn normalize() {
+    assert_eq!(
+        jsonxf::normalize("{\n  \"z\": [3, {\"y\": 1, \"x\": 2}],\n  \"a\": \"s p\"\n}").unwrap(),
+        "{\"a\":\"s p\",\"z\":[3,{\"x\":2,\"y\":1}]}"
+    );
+    assert_ne!(
+        jsonxf::normalize("[1, 2]").unwrap(),
+        jsonxf::normalize("[2, 1]").unwrap()
+    );
+    assert_ne!(
+        jsonxf::normalize("1.0").unwrap(),
+        jsonxf::normalize("1").unwrap()
+    );
+}
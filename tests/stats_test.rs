@@ -0,0 +1,98 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, Stats};
+
+fn stats(mut xf: Formatter, input: &str) -> Stats {
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream_with_stats(input.as_bytes(), &mut output)
+        .unwrap()
+}
+
+#[test]
+fn counts_structures_and_strings() {
+    let input = "{\"a\": [{\"b\": \"x\"}, [], \"[\\\"{\"], \"c\": {}}\n[\"y\"] \"z\"";
+    assert_eq!(
+        stats(Formatter::minimizer(), input),
+        Stats {
+            input_bytes: input.len(),
+            output_bytes: jsonxf::minimize(input).unwrap().len(),
+            documents: 3,
+            max_depth: 3,
+            objects: 3,
+            arrays: 3,
+            strings: 4,
+            string_chars: 7,
+            longest_string: 4,
+        }
+    );
+}
+
+#[test]
+fn counts_pretty_printed_output() {
+    let input = "[1,[2]]";
+    let s = stats(Formatter::pretty_printer(), input);
+    assert_eq!(s.input_bytes, 7);
+    assert_eq!(s.output_bytes, jsonxf::pretty_print(input).unwrap().len());
+    assert_eq!((s.max_depth, s.arrays), (2, 2));
+}
+
+#[test]
+fn stats_with_sort_keys() {
+    let mut xf = Formatter::minimizer();
+    xf.sort_keys = true;
+    let s = stats(xf, "{\"b\": [1], \"a\": \"x\"}");
+    assert_eq!((s.input_bytes, s.output_bytes), (20, 17));
+    assert_eq!((s.max_depth, s.objects, s.arrays, s.strings), (2, 1, 1, 1));
+}
+
+#[test]
+fn stats_are_reset() {
+    let mut xf = Formatter::minimizer();
+    let mut output: Vec<u8> = vec![];
+    xf.format_stream_with_stats("[[[]]]".as_bytes(), &mut output)
+        .unwrap();
+    let s = xf
+        .format_stream_with_stats("[]".as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!((s.input_bytes, s.max_depth, s.arrays), (2, 1, 1));
+}
+
+#[test]
+fn counts_characters_in_strings() {
+    let input = r#"{"key": "h\u00e9llo"} ["\u00e9\u00e8", "😀é!"]"#;
+    let s = stats(Formatter::minimizer(), input);
+    assert_eq!((s.strings, s.string_chars, s.longest_string), (3, 25, 12));
+
+    // Escapes count as one character once decoded
+    let mut xf = Formatter::minimizer();
+    xf.unescape_unicode = true;
+    let s = stats(xf, input);
+    assert_eq!((s.strings, s.string_chars, s.longest_string), (3, 10, 5));
+
+    let mut xf = Formatter::minimizer();
+    xf.unescape_unicode = true;
+    let s = stats(xf, r#"["\ud83d\ude00\n"]"#);
+    assert_eq!((s.string_chars, s.longest_string), (3, 3));
+
+    for &ascii_only in [false, true].iter() {
+        let mut xf = Formatter::minimizer();
+        xf.ascii_only = ascii_only;
+        let s = stats(xf, r#"["é\"x", "😀"]"#);
+        assert_eq!((s.string_chars, s.longest_string), (5, 4));
+    }
+}
+
+#[test]
+fn counts_documents() {
+    let input = "{\"a\": [1, 2]} [3]\n\"x\" 4 null {}";
+    for xf in [Formatter::pretty_printer(), Formatter::minimizer()].iter() {
+        assert_eq!(stats(xf.clone(), input).documents, 6);
+
+        let mut sorted = xf.clone();
+        sorted.sort_keys = true;
+        assert_eq!(stats(sorted, input).documents, 6);
+    }
+    assert_eq!(stats(Formatter::minimizer(), "").documents, 0);
+    let mut xf = Formatter::minimizer();
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    assert_eq!(stats(xf, "[[1], 2] /* x */").documents, 1);
+}
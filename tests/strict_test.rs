@@ -0,0 +1,302 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, JsonxfError};
+
+fn strict_minimize(input: &str) -> Result<String, JsonxfError> {
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    xf.format(input)
+}
+
+#[test]
+fn balanced_input_is_formatted() {
+    assert_eq!(
+        strict_minimize("[1, {\"a\": []}]").unwrap(),
+        "[1,{\"a\":[]}]"
+    );
+}
+
+#[test]
+fn unclosed_structures() {
+    match strict_minimize("[1, [2") {
+        Err(JsonxfError::UnexpectedEnd {
+            depth: 2,
+            offset: 6,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn unexpected_close() {
+    match strict_minimize("[1]]") {
+        Err(JsonxfError::UnexpectedClose { offset: 3 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn mismatched_close() {
+    match strict_minimize("{\"a\": [1}]") {
+        Err(JsonxfError::MismatchedClose {
+            expected: ']',
+            found: '}',
+            offset: 8,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(
+        strict_minimize("[{]").unwrap_err().to_string(),
+        "expected `}` but found `]` at byte 2"
+    );
+    assert_eq!(jsonxf::minimize("[1}").unwrap(), "[1}");
+}
+
+#[test]
+fn check_reports_mismatched_close() {
+    match jsonxf::check(&b"{ ]"[..]) {
+        Err(JsonxfError::MismatchedClose {
+            expected: '}',
+            found: ']',
+            offset: 2,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(jsonxf::check(&b"[ }"[..]).is_err());
+    assert!(jsonxf::check(&b"[{\"]\": \"}\"}]"[..]).is_ok());
+}
+
+#[test]
+fn stray_closers_do_not_underflow() {
+    assert_eq!(jsonxf::minimize("]}").unwrap(), "]}");
+    assert_eq!(jsonxf::pretty_print("]}").unwrap(), "\n]\n}");
+    match strict_minimize("]}") {
+        Err(JsonxfError::UnexpectedClose { offset: 0 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn max_depth() {
+    let mut xf = Formatter::pretty_printer();
+    xf.max_depth = Some(2);
+    assert_eq!(
+        xf.format("[[1], []]").unwrap(),
+        "[\n  [\n    1\n  ],\n  []\n]"
+    );
+
+    let mut xf = Formatter::pretty_printer();
+    xf.max_depth = Some(2);
+    match xf.format("[[{}]]") {
+        Err(JsonxfError::MaxDepthExceeded {
+            depth: 3,
+            offset: 2,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let deep = "[".repeat(10_000);
+    assert!(jsonxf::minimize(&deep).is_ok());
+}
+
+#[test]
+fn unterminated_strings() {
+    let test_cases = vec![
+        ("\"abc", 0),
+        ("[1, \"a\", \"b", 9),
+        ("{\"a\\\"", 1),
+        ("\"ab\\", 0),
+    ];
+    for (input, offset) in test_cases {
+        match strict_minimize(input) {
+            Err(JsonxfError::UnterminatedString { offset: o }) if o == offset => {}
+            other => panic!("unexpected result for {:?}: {:?}", input, other),
+        }
+    }
+
+    let err = strict_minimize("[\"abc").unwrap_err();
+    assert_eq!(err.to_string(), "unterminated string starting at byte 1");
+
+    let mut xf = Formatter::minimizer();
+    assert_eq!(xf.format("[\"abc").unwrap(), "[\"abc");
+}
+
+#[test]
+fn control_characters_in_strings() {
+    match strict_minimize("{\"a\": \"line\nbreak\"}") {
+        Err(JsonxfError::ControlCharacter {
+            byte: b'\n',
+            offset: 11,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    xf.canonicalize_string_whitespace = true;
+    assert_eq!(xf.format("[\"a\tb\"]").unwrap(), "[\"a\\tb\"]");
+
+    let err = strict_minimize("[\"\\\t\"]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unescaped control character 0x09 in string at byte 3"
+    );
+
+    assert_eq!(
+        strict_minimize("[\"tab\\there\", \"é\\u0000\",\n\t1]").unwrap(),
+        "[\"tab\\there\",\"é\\u0000\",1]"
+    );
+
+    let mut xf = Formatter::minimizer();
+    assert_eq!(xf.format("[\"a\tb\"]").unwrap(), "[\"a\tb\"]");
+}
+
+#[test]
+fn invalid_utf8_in_strings() {
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    let cases: [(&[u8], usize); 6] = [
+        (b"[\"ab\x80\"]", 4),               // stray continuation byte
+        (b"[\"\xc3(\"]", 2),                // lead byte without continuation
+        (b"[\"x\xe2\x82\"]", 3),            // cut short by the closing quote
+        (b"[\"\xc0\xaf\"]", 2),             // invalid lead byte
+        (b"[\"\xe0\x80\xaf\"]", 2),         // overlong form
+        (b"[\"ok\", \"\xed\xa0\x80\"]", 8), // surrogate
+    ];
+    for &(input, offset) in cases.iter() {
+        match xf.format_bytes(input) {
+            Err(JsonxfError::InvalidUtf8 { offset: o }) if o == offset => {}
+            other => panic!("unexpected result for {:?}: {:?}", input, other),
+        }
+    }
+
+    let err = xf.format_bytes(b"[\"\xff\"]").unwrap_err();
+    assert_eq!(err.to_string(), "invalid UTF-8 in string at byte 2");
+
+    // Valid characters, including ones split across buffers, are fine
+    assert_eq!(
+        xf.format_bytes("[\"é€😀\"]".as_bytes()).unwrap(),
+        "[\"é€😀\"]".as_bytes()
+    );
+    let mut output: Vec<u8> = vec![];
+    xf.reset();
+    xf.format_buf(b"[\"\xf0\x9f", &mut output).unwrap();
+    xf.format_buf(b"\x98\x80\"]", &mut output).unwrap();
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, "[\"😀\"]".as_bytes());
+
+    // Without strict mode, invalid UTF-8 passes through
+    let mut xf = Formatter::minimizer();
+    assert_eq!(
+        xf.format_bytes(b"[ \"\xc3(\xff\" ]").unwrap(),
+        b"[\"\xc3(\xff\"]"
+    );
+    xf.ascii_only = true;
+    assert_eq!(
+        xf.format_bytes(b"[ \"\xc3(\xc3\xa9\" ]").unwrap(),
+        b"[\"\xc3(\\u00e9\"]"
+    );
+}
+
+#[test]
+fn check_without_output() {
+    assert!(jsonxf::check(&b"{\"a\": [1, {}], \"b\": \"}\"} [2] 3"[..]).is_ok());
+    assert!(jsonxf::check(&b""[..]).is_ok());
+    match jsonxf::check(&b"[1]]"[..]) {
+        Err(JsonxfError::UnexpectedClose { offset: 3 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match jsonxf::check(&b"[[\"a"[..]) {
+        Err(JsonxfError::UnterminatedString { offset: 2 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match jsonxf::check(&b"{\"a\": [1]"[..]) {
+        Err(JsonxfError::UnexpectedEnd {
+            depth: 1,
+            offset: 9,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn single_document() {
+    let mut xf = Formatter::pretty_printer();
+    xf.single_document = true;
+    assert_eq!(xf.format(" [1, 2] \n\t").unwrap(), "[\n  1,\n  2\n]");
+    assert_eq!(xf.format("\"a b\"").unwrap(), "\"a b\"");
+    for &(input, at) in [
+        ("{\"a\":1} garbage", 8),
+        ("[1]\n[2]", 4),
+        ("1 2", 2),
+        ("\"a\"\"b\"", 3),
+        ("true]", 4),
+        ("{},", 2),
+    ]
+    .iter()
+    {
+        match xf.format(input) {
+            Err(JsonxfError::TrailingData { offset }) if offset == at => {}
+            other => panic!("unexpected result for {:?}: {:?}", input, other),
+        }
+    }
+    assert_eq!(
+        xf.format("1 2").unwrap_err().to_string(),
+        "unexpected data after the first value at byte 2"
+    );
+}
+
+#[test]
+fn single_document_with_comments_and_sorting() {
+    let mut xf = Formatter::minimizer();
+    xf.single_document = true;
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    xf.sort_keys = true;
+    assert_eq!(
+        xf.format("{\"b\": 1, \"a\": 2} // done\n").unwrap(),
+        "{\"a\":2,\"b\":1}"
+    );
+    match xf.format("{} /* x */ {}") {
+        Err(JsonxfError::TrailingData { offset: 11 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn reject_leading_junk() {
+    let mut xf = Formatter::minimizer();
+    xf.reject_leading_junk = true;
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    for input in [
+        "{}",
+        "[]",
+        "\"a\"",
+        "-1",
+        "0",
+        "true",
+        "false",
+        "null",
+        " \n\t/* x */ 1",
+    ]
+    .iter()
+    {
+        assert!(xf.format(input).is_ok(), "{:?}", input);
+    }
+    for &(input, byte, at) in [
+        ("xx{}", b'x', 0),
+        ("  ]", b']', 2),
+        ("\u{1}[]", 1, 0),
+        ("'a'", b'\'', 0),
+    ]
+    .iter()
+    {
+        match xf.format(input) {
+            Err(JsonxfError::LeadingJunk { byte: b, offset }) if b == byte && offset == at => {}
+            other => panic!("unexpected result for {:?}: {:?}", input, other),
+        }
+    }
+
+    // Only the start of the input is checked
+    assert_eq!(xf.format("1 x").unwrap(), "1\nx");
+    xf.single_quotes = true;
+    assert_eq!(xf.format("'a'").unwrap(), "\"a\"");
+}
@@ -0,0 +1,352 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn ascii_only(input: &str) -> String {
+    let mut xf = Formatter::minimizer();
+    xf.ascii_only = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn ascii_only_escapes_non_ascii() {
+    assert_eq!(
+        ascii_only("{\"café\": \"naïve ☃\"}"),
+        "{\"caf\\u00e9\":\"na\\u00efve \\u2603\"}"
+    );
+}
+
+#[test]
+fn ascii_only_uses_surrogate_pairs() {
+    assert_eq!(ascii_only("[\"😀\"]"), "[\"\\ud83d\\ude00\"]");
+}
+
+#[test]
+fn ascii_only_keeps_existing_escapes() {
+    assert_eq!(
+        ascii_only("[\"\\u00e9\\\"é\\\\\"]"),
+        "[\"\\u00e9\\\"\\u00e9\\\\\"]"
+    );
+}
+
+#[test]
+fn ascii_only_across_buffers() {
+    let mut xf = Formatter::minimizer();
+    xf.ascii_only = true;
+    let mut output: Vec<u8> = vec![];
+    let input = "[\"é😀\"]".as_bytes();
+    for chunk in input.chunks(1) {
+        xf.format_buf(chunk, &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"[\"\\u00e9\\ud83d\\ude00\"]");
+}
+
+#[test]
+fn ascii_only_passes_invalid_utf8_through() {
+    let mut xf = Formatter::minimizer();
+    xf.ascii_only = true;
+    assert_eq!(
+        xf.format_bytes(b"[\"\xff\xc3\" , \"\xe2\x98\"]").unwrap(),
+        b"[\"\xff\xc3\",\"\xe2\x98\"]"
+    );
+}
+
+fn unescape(input: &str) -> String {
+    let mut xf = Formatter::minimizer();
+    xf.unescape_unicode = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn unescape_unicode_decodes_escapes() {
+    assert_eq!(
+        unescape("{\"caf\\u00e9\": \"na\\u00EFve \\u2603\"}"),
+        "{\"café\":\"naïve ☃\"}"
+    );
+}
+
+#[test]
+fn unescape_unicode_decodes_surrogate_pairs() {
+    assert_eq!(unescape("[\"\\uD83D\\uDE00!\"]"), "[\"😀!\"]");
+}
+
+#[test]
+fn unescape_unicode_keeps_required_escapes() {
+    assert_eq!(
+        unescape("[\"\\u0022\\u005c\\u000a\\n\\\"\\\\\"]"),
+        "[\"\\u0022\\u005c\\u000a\\n\\\"\\\\\"]"
+    );
+}
+
+#[test]
+fn unescape_unicode_passes_invalid_escapes_through() {
+    assert_eq!(
+        unescape("[\"\\u12\", \"\\u12x\"]"),
+        "[\"\\u12\",\"\\u12x\"]"
+    );
+    assert_eq!(unescape("[\"\\uDE00\"]"), "[\"\\uDE00\"]");
+    assert_eq!(unescape("[\"\\uD83D\"]"), "[\"\\uD83D\"]");
+    assert_eq!(unescape("[\"\\uD83D\\n\"]"), "[\"\\uD83D\\n\"]");
+    assert_eq!(unescape("[\"\\uD83D\\u00e9\"]"), "[\"\\uD83Dé\"]");
+    assert_eq!(unescape("[\"\\uD83D\\uD83D\\uDE00\"]"), "[\"\\uD83D😀\"]");
+}
+
+#[test]
+fn unescape_unicode_across_buffers() {
+    let mut xf = Formatter::minimizer();
+    xf.unescape_unicode = true;
+    let mut output: Vec<u8> = vec![];
+    let input = b"[\"\\u00e9\\ud83d\\ude00\\\\u0041\"]";
+    for chunk in input.chunks(1) {
+        xf.format_buf(chunk, &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, "[\"é😀\\\\u0041\"]".as_bytes());
+}
+
+#[test]
+fn unescape_unicode_with_ascii_only() {
+    let mut xf = Formatter::minimizer();
+    xf.unescape_unicode = true;
+    xf.ascii_only = true;
+    assert_eq!(
+        xf.format("[\"\\u0041\\u00e9é\"]").unwrap(),
+        "[\"A\\u00e9\\u00e9\"]"
+    );
+}
+
+#[test]
+fn long_strings_across_buffers() {
+    // Longer than the internal read buffer, with escapes landing on
+    // every possible boundary
+    let contents = "abc\\\"\\\\def\\n".repeat(4000);
+    let input = format!("[ \"{}\" , \"{}\\\\\" ]", contents, contents);
+    let output = format!("[\"{}\",\"{}\\\\\"]", contents, contents);
+
+    let mut reader = input.as_bytes();
+    let mut result: Vec<u8> = vec![];
+    jsonxf::minimize_stream(&mut reader, &mut result).unwrap();
+    assert_eq!(output.as_bytes(), &result[..]);
+}
+
+fn single_quotes(input: &str) -> String {
+    let mut xf = Formatter::minimizer();
+    xf.single_quotes = true;
+    xf.format(input).unwrap()
+}
+
+#[test]
+fn single_quoted_strings() {
+    assert_eq!(single_quotes("{'a': 'b'}"), "{\"a\":\"b\"}");
+    assert_eq!(
+        single_quotes(r#"['say "hi"', 'it\'s', "it's", 'a\\b\n', '']"#),
+        r#"["say \"hi\"","it's","it's","a\\b\n",""]"#
+    );
+    assert_eq!(single_quotes(r#"["a'b", 'c"d']"#), r#"["a'b","c\"d"]"#);
+
+    // Without the option, single quotes are left alone
+    assert_eq!(jsonxf::minimize("['a b']").unwrap(), "['a b']");
+}
+
+#[test]
+fn single_quoted_strings_with_other_options() {
+    let mut xf = Formatter::pretty_printer();
+    xf.single_quotes = true;
+    xf.sort_keys = true;
+    xf.ascii_only = true;
+    assert_eq!(
+        xf.format("{'é': 1, 'a': '\"'}").unwrap(),
+        "{\n  \"\\u00e9\": 1,\n  \"a\": \"\\\"\"\n}"
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.single_quotes = true;
+    let mut output: Vec<u8> = vec![];
+    for chunk in ["['ab", "\\", "'c", "\"", "'", ", 'x'", "]"].iter() {
+        xf.format_buf(chunk.as_bytes(), &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"[\"ab'c\\\"\",\"x\"]");
+}
+
+#[test]
+fn unquoted_keys_are_quoted() {
+    let mut xf = Formatter::pretty_printer();
+    xf.quote_keys = true;
+    assert_eq!(
+        xf.format("{a: 1, b: [x, {c:null}], \"d\": e, 1: f}").unwrap(),
+        "{\n  \"a\": 1,\n  \"b\": [\n    x,\n    {\n      \"c\": null\n    }\n  ],\n  \"d\": e,\n  \"1\": f\n}"
+    );
+
+    // Together with the other JSON5 options
+    let mut xf = Formatter::minimizer();
+    xf.quote_keys = true;
+    xf.single_quotes = true;
+    xf.normalize_numbers = true;
+    xf.sort_keys = true;
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    assert_eq!(
+        xf.format("{zed: 'z', // last\n alpha: 1.50, 'mid': [1e+02]}")
+            .unwrap(),
+        "{\"alpha\":1.5,\"mid\":[1e2],\"zed\":\"z\"}"
+    );
+
+    let mut xf = Formatter::minimizer();
+    xf.quote_keys = true;
+    let mut output: Vec<u8> = vec![];
+    for chunk in ["{ab", "c", ":1,d", "\n:2}"].iter() {
+        xf.format_buf(chunk.as_bytes(), &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, b"{\"abc\":1,\"d\":2}");
+}
+
+#[test]
+fn escape_slashes() {
+    let mut xf = Formatter::minimizer();
+    xf.escape_slashes = true;
+    assert_eq!(
+        xf.format("{\"a/b\": \"//\\/\", \"c\": 1 / 2}").unwrap(),
+        "{\"a\\/b\":\"\\/\\/\\/\",\"c\":1/2}"
+    );
+    xf.unescape_unicode = true;
+    assert_eq!(xf.format("\"\\u002f\\u0041\"").unwrap(), "\"\\/A\"");
+}
+
+#[test]
+fn escape_html() {
+    let mut xf = Formatter::pretty_printer();
+    xf.escape_html = true;
+    assert_eq!(
+        xf.format("{\"<a&b>\": \"x<y>z&\", \"c\": [1, \"\\\\<\"]}").unwrap(),
+        "{\n  \"\\u003ca\\u0026b\\u003e\": \"x\\u003cy\\u003ez\\u0026\",\n  \"c\": [\n    1,\n    \"\\\\\\u003c\"\n  ]\n}"
+    );
+
+    xf.remove_needless_escapes = true;
+    xf.hex_case = jsonxf::HexCase::Upper;
+    assert_eq!(
+        xf.format("\"\\u003c\\u0041>\"").unwrap(),
+        "\"\\u003CA\\u003E\""
+    );
+}
+
+#[test]
+fn escape_slashes_with_comments() {
+    let mut xf = Formatter::minimizer();
+    xf.escape_slashes = true;
+    xf.comment_mode = jsonxf::CommentMode::Strip;
+    assert_eq!(
+        xf.format("[\"http://x\", // a comment\n \"/*\"] /* done */")
+            .unwrap(),
+        "[\"http:\\/\\/x\",\"\\/*\"]"
+    );
+}
+
+#[test]
+fn remove_needless_escapes() {
+    let mut xf = Formatter::minimizer();
+    xf.remove_needless_escapes = true;
+    assert_eq!(
+        xf.format(r#"["\/\u0041\u00e9", "\\/", "\"\\\b\f\n\r\t", "\u001f\u005c\ud83d\ude00"]"#)
+            .unwrap(),
+        "[\"/Aé\",\"\\\\/\",\"\\\"\\\\\\b\\f\\n\\r\\t\",\"\\u001f\\u005c😀\"]"
+    );
+    xf.escape_slashes = true;
+    assert_eq!(xf.format(r#""/\/""#).unwrap(), r#""\/\/""#);
+}
+
+#[test]
+fn canonicalize_string_whitespace() {
+    let mut xf = Formatter::pretty_printer();
+    xf.canonicalize_string_whitespace = true;
+    assert_eq!(
+        xf.format("{\"a\tb\": \"line\r\nnext\u{8}\u{c}\u{1f}\\\\\\n\\t\"}")
+            .unwrap(),
+        "{\n  \"a\\tb\": \"line\\r\\nnext\\b\\f\\u001f\\\\\\n\\t\"\n}"
+    );
+    let stats = {
+        let mut output: Vec<u8> = vec![];
+        xf.format_stream_with_stats(&b"\"\t\""[..], &mut output)
+            .unwrap()
+    };
+    assert_eq!(stats.string_chars, 2);
+}
+
+#[test]
+fn lone_backslash_at_end_of_input() {
+    let input = "\"abc\\";
+    let mut formatters = vec![];
+    for base in [Formatter::pretty_printer(), Formatter::minimizer()].iter() {
+        formatters.push(base.clone());
+        let mut xf = base.clone();
+        xf.ascii_only = true;
+        formatters.push(xf);
+        let mut xf = base.clone();
+        xf.unescape_unicode = true;
+        formatters.push(xf);
+        let mut xf = base.clone();
+        xf.remove_needless_escapes = true;
+        xf.escape_slashes = true;
+        formatters.push(xf);
+        let mut xf = base.clone();
+        xf.canonicalize_string_whitespace = true;
+        formatters.push(xf);
+        let mut xf = base.clone();
+        xf.single_quotes = true;
+        formatters.push(xf);
+        let mut xf = base.clone();
+        xf.sort_keys = true;
+        formatters.push(xf);
+    }
+    for xf in formatters.iter_mut() {
+        assert_eq!(xf.format(input).unwrap(), input, "{:?}", xf);
+
+        // The same output however the input is split up
+        let mut output: Vec<u8> = vec![];
+        xf.reset();
+        for b in input.bytes() {
+            xf.format_buf(&[b], &mut output).unwrap();
+        }
+        xf.finish(&mut output).unwrap();
+        assert_eq!(output, input.as_bytes(), "{:?}", xf);
+
+        xf.strict = true;
+        match xf.format(input) {
+            Err(jsonxf::JsonxfError::UnterminatedString { offset: 0 }) => {}
+            other => panic!("{:?}: {:?}", xf, other),
+        }
+    }
+}
+
+#[test]
+fn hex_case() {
+    use jsonxf::HexCase;
+    let input = "[\"\\u00E9\\uD83D\\uDE00\\uDc00\\uD800\\n\\uAB\\\\uABCD\", \"\\uFFFF\"]";
+    let mut xf = Formatter::minimizer();
+    xf.hex_case = HexCase::Lower;
+    let expected = "[\"\\u00e9\\ud83d\\ude00\\udc00\\ud800\\n\\uAB\\\\uABCD\",\"\\uffff\"]";
+    assert_eq!(xf.format(input).unwrap(), expected);
+
+    // The same, a byte at a time
+    let mut output: Vec<u8> = vec![];
+    xf.reset();
+    for b in input.as_bytes() {
+        xf.format_buf(&[*b], &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    xf.hex_case = HexCase::Upper;
+    assert_eq!(xf.format("\"\\u00e9\"").unwrap(), "\"\\u00E9\"");
+    assert_eq!(xf.format("\"\\ud800").unwrap(), "\"\\uD800");
+
+    // Escapes kept or written by other options follow it too
+    xf.unescape_unicode = true;
+    xf.ascii_only = true;
+    xf.canonicalize_string_whitespace = true;
+    assert_eq!(
+        xf.format("\"\\u0041\\u001f\\u00e9\u{1f600}\u{1}\"")
+            .unwrap(),
+        "\"A\\u001F\\u00E9\\uD83D\\uDE00\\u0001\""
+    );
+}
@@ -0,0 +1,94 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, Toggle};
+
+#[test]
+fn toggles_between_styles() {
+    assert_eq!(
+        jsonxf::reformat("{\"a\":{\"b\":[1,2]}}").unwrap(),
+        "{\n  \"a\": {\n    \"b\": [\n      1,\n      2\n    ]\n  }\n}"
+    );
+    assert_eq!(
+        jsonxf::reformat("{\n  \"a\": {\n    \"b\": [\n      1,\n      2\n    ]\n  }\n}").unwrap(),
+        "{\"a\":{\"b\":[1,2]}}"
+    );
+    assert_eq!(jsonxf::reformat("\r\n[\r\n\t1 ]").unwrap(), "[1]");
+    assert_eq!(jsonxf::reformat("[ 1, 2 ]").unwrap(), "[\n  1,\n  2\n]");
+}
+
+#[test]
+fn ignores_brackets_in_strings() {
+    assert!(!jsonxf::looks_pretty(b"\"[\\\"{\n\" [1]"));
+    assert!(jsonxf::looks_pretty(b"\"[\\\"{\n\" [\n1]"));
+}
+
+#[test]
+fn scalars_and_empty_input_look_minimized() {
+    for input in [&b""[..], b"42", b"\"a\"\n", b"[", b"[]", b"{ }"].iter() {
+        assert!(!jsonxf::looks_pretty(input), "{:?}", input);
+    }
+}
+
+#[test]
+fn styles_and_test_are_configurable() {
+    let mut toggle = Toggle::new();
+    toggle.pretty_printer.indent = String::from("\t");
+    toggle.minimizer = Formatter::spaced_minimizer();
+    assert_eq!(
+        toggle.format("[1,[2]]").unwrap(),
+        "[\n\t1,\n\t[\n\t\t2\n\t]\n]"
+    );
+    assert_eq!(toggle.format("[\n1,\n[2]\n]").unwrap(), "[1, [2]]");
+
+    // Only consider the first line
+    toggle.looks_pretty = |sample| sample.contains(&b'\n');
+    toggle.sample_length = 4;
+    assert_eq!(toggle.format("[1, \n2]").unwrap(), "[\n\t1,\n\t2\n]");
+    assert_eq!(toggle.format("[1,\n2]").unwrap(), "[1, 2]");
+}
+
+#[test]
+fn is_minimized() {
+    for input in [
+        "",
+        "1",
+        "[1,{\"a\":[]}]",
+        "\"a b\\\" c\"",
+        "[1]\n[2]",
+        "1\n2",
+    ]
+    .iter()
+    {
+        assert!(jsonxf::is_minimized(input), "{:?}", input);
+    }
+    for input in [
+        " 1",
+        "1 ",
+        "[1, 2]",
+        "[\n1]",
+        "[1]\n",
+        "\n[1]",
+        "[1]\n\n[2]",
+        "[1]\r\n[2]",
+        "{\"a\" :1}",
+    ]
+    .iter()
+    {
+        assert!(!jsonxf::is_minimized(input), "{:?}", input);
+    }
+
+    let mut xf = Formatter::minimizer();
+    let input = "{ \"a\": [ 1, \"x\\\"y\" ] }\n\n[2]";
+    assert!(jsonxf::is_minimized(&xf.format(input).unwrap()));
+}
+
+#[test]
+fn detect_indent() {
+    use jsonxf::detect_indent;
+    assert_eq!(detect_indent(b"{\r\n  \"a\": 1\r\n}").unwrap(), "  ");
+    assert_eq!(detect_indent(b"\"[\\\"\" [\n\t[\n").unwrap(), "\t");
+    assert_eq!(detect_indent(b"{}\n[ ]\n[\n   1]").unwrap(), "   ");
+    assert_eq!(detect_indent(b"[\n1,\n  2]"), None);
+    assert_eq!(detect_indent(b"[[\n    1]]"), None);
+    assert_eq!(detect_indent(b"[\n"), None);
+    assert_eq!(detect_indent(b"1 2"), None);
+}
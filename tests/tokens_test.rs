@@ -0,0 +1,83 @@
+extern crate jsonxf;
+use jsonxf::Token;
+use std::ops::{ControlFlow, Range};
+
+fn tokens(input: &str) -> Vec<(Token<'_>, Range<usize>)> {
+    let mut tokens = vec![];
+    let result = jsonxf::tokenize(input, |token, range| {
+        tokens.push((token, range));
+        ControlFlow::Continue(())
+    });
+    assert_eq!(result, ControlFlow::Continue(()));
+    tokens
+}
+
+#[test]
+fn tokenizes_structure_and_values() {
+    assert_eq!(
+        tokens("{\"a\": [1, -2.5e3], \"b\\\"\": {\"c\": \"x\\\\\"}, \"d\": true}"),
+        vec![
+            (Token::ObjectStart, 0..1),
+            (Token::Key("a"), 1..4),
+            (Token::Colon, 4..5),
+            (Token::ArrayStart, 6..7),
+            (Token::ValueNumber("1"), 7..8),
+            (Token::Comma, 8..9),
+            (Token::ValueNumber("-2.5e3"), 10..16),
+            (Token::ArrayEnd, 16..17),
+            (Token::Comma, 17..18),
+            (Token::Key("b\\\""), 19..24),
+            (Token::Colon, 24..25),
+            (Token::ObjectStart, 26..27),
+            (Token::Key("c"), 27..30),
+            (Token::Colon, 30..31),
+            (Token::ValueString("x\\\\"), 32..37),
+            (Token::ObjectEnd, 37..38),
+            (Token::Comma, 38..39),
+            (Token::Key("d"), 40..43),
+            (Token::Colon, 43..44),
+            (Token::ValueLiteral("true"), 45..49),
+            (Token::ObjectEnd, 49..50),
+        ]
+    );
+}
+
+#[test]
+fn strings_in_arrays_are_values() {
+    assert_eq!(
+        tokens("[\"é\"]\n\"x\" null"),
+        vec![
+            (Token::ArrayStart, 0..1),
+            (Token::ValueString("é"), 1..5),
+            (Token::ArrayEnd, 5..6),
+            (Token::ValueString("x"), 7..10),
+            (Token::ValueLiteral("null"), 11..15),
+        ]
+    );
+}
+
+#[test]
+fn unterminated_strings_run_to_the_end() {
+    assert_eq!(
+        tokens("[\"ab\\\""),
+        vec![
+            (Token::ArrayStart, 0..1),
+            (Token::ValueString("ab\\\""), 1..6)
+        ]
+    );
+}
+
+#[test]
+fn callback_can_stop() {
+    let mut count = 0;
+    let result = jsonxf::tokenize("[1, 2, 3]", |token, _| {
+        count += 1;
+        if token == Token::ValueNumber("2") {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(result, ControlFlow::Break(()));
+    assert_eq!(count, 4);
+}
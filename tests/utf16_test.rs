@@ -0,0 +1,110 @@
+extern crate jsonxf;
+use jsonxf::Formatter;
+
+fn utf16(s: &str, big_endian: bool) -> Vec<u8> {
+    let mut bytes = vec![];
+    for unit in s.encode_utf16() {
+        if big_endian {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        } else {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn decoder() -> Formatter {
+    let mut xf = Formatter::minimizer();
+    xf.decode_utf16 = true;
+    xf
+}
+
+#[test]
+fn transcodes_both_byte_orders() {
+    let input = "\u{feff}{ \"k\u{e9}y\": [\"\u{1f600}\", 1] }";
+    let expected = "{\"k\u{e9}y\":[\"\u{1f600}\",1]}";
+    assert_eq!(
+        decoder().format_bytes(&utf16(input, false)).unwrap(),
+        expected.as_bytes()
+    );
+    assert_eq!(
+        decoder().format_bytes(&utf16(input, true)).unwrap(),
+        expected.as_bytes()
+    );
+
+    let mut xf = decoder();
+    xf.preserve_bom = true;
+    assert_eq!(
+        xf.format_bytes(&utf16(input, true)).unwrap(),
+        format!("\u{feff}{}", expected).as_bytes()
+    );
+}
+
+#[test]
+fn transcodes_one_byte_at_a_time() {
+    let input = utf16("\u{feff}[\"a\u{1f600}b\", {\"c\": null}]", false);
+    let mut xf = decoder();
+    let mut output: Vec<u8> = vec![];
+    for b in input.iter() {
+        xf.format_buf(&[*b], &mut output).unwrap();
+    }
+    xf.finish(&mut output).unwrap();
+    assert_eq!(output, "[\"a\u{1f600}b\",{\"c\":null}]".as_bytes());
+}
+
+#[test]
+fn replaces_unpaired_surrogates() {
+    let mut input = utf16("\u{feff}[\"", false);
+    input.extend_from_slice(&[0x3d, 0xd8, b'x', 0, 0x00, 0xde]);
+    input.extend_from_slice(&utf16("\"]", false));
+    assert_eq!(
+        decoder().format_bytes(&input).unwrap(),
+        "[\"\u{fffd}x\u{fffd}\"]".as_bytes()
+    );
+
+    let mut input = utf16("\u{feff}1", true);
+    input.push(0);
+    assert_eq!(
+        decoder().format_bytes(&input).unwrap(),
+        "1\u{fffd}".as_bytes()
+    );
+}
+
+#[test]
+fn leaves_other_input_alone() {
+    assert_eq!(decoder().format_bytes(b"[ 1 ]").unwrap(), b"[1]");
+    assert_eq!(decoder().format_bytes(b"1").unwrap(), b"1");
+    assert_eq!(
+        decoder().format_bytes(b"\xef\xbb\xbf[ 1 ]").unwrap(),
+        b"[1]"
+    );
+
+    let mut xf = decoder();
+    xf.sort_keys = true;
+    assert_eq!(
+        xf.format_bytes(&utf16("\u{feff}{\"b\": 1, \"a\": 2}", false))
+            .unwrap(),
+        b"{\"a\":2,\"b\":1}"
+    );
+}
+
+#[test]
+fn finds_pointers_in_utf16() {
+    let input = utf16("\u{feff}{\"a\u{e9}\": [1, {\"b\": 2}]}", true);
+    assert_eq!(
+        decoder().format_pointer(&input, "/a\u{e9}/1").unwrap(),
+        "{\"b\":2}"
+    );
+    assert!(Formatter::minimizer()
+        .format_pointer(&input, "/a\u{e9}")
+        .is_err());
+}
+
+#[test]
+fn finds_duplicate_keys_in_utf16() {
+    let input = utf16("\u{feff}[{\"\u{e9}\": 1, \"\u{e9}\": 2}]", false);
+    assert_eq!(
+        decoder().find_duplicate_keys(&input).unwrap(),
+        vec![(String::from("/0"), String::from("\u{e9}"))]
+    );
+}
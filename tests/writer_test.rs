@@ -0,0 +1,47 @@
+extern crate jsonxf;
+use jsonxf::{Formatter, JsonxfError, PrettyWriter};
+use std::io::{self, Write};
+
+#[test]
+fn formats_while_writing() {
+    let mut writer = PrettyWriter::new(vec![], "  ");
+    for chunk in b"{\"a\":[1,\"x\"],\"b\":{}}".chunks(3) {
+        writer.write_all(chunk).unwrap();
+    }
+    let output = writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\n  \"a\": [\n    1,\n    \"x\"\n  ],\n  \"b\": {}\n}"
+    );
+}
+
+#[test]
+fn finishes_when_dropped() {
+    let mut output: Vec<u8> = vec![];
+    {
+        let mut xf = Formatter::minimizer();
+        xf.sort_keys = true;
+        let mut writer = PrettyWriter::with_formatter(&mut output, xf);
+        write!(writer, "{{ \"b\": 1, \"a\": 2 }}").unwrap();
+        assert!(writer.get_ref().is_empty());
+    }
+    assert_eq!(output, b"{\"a\":2,\"b\":1}");
+}
+
+#[test]
+fn reports_errors() {
+    let mut xf = Formatter::minimizer();
+    xf.max_depth = Some(1);
+    let mut writer = PrettyWriter::with_formatter(vec![], xf);
+    let err = writer.write_all(b"[[1]]").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut xf = Formatter::minimizer();
+    xf.strict = true;
+    let mut writer = PrettyWriter::with_formatter(vec![], xf);
+    writer.write_all(b"[1").unwrap();
+    match writer.finish() {
+        Err(JsonxfError::UnexpectedEnd { depth: 1, .. }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}